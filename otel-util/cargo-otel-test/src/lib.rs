@@ -0,0 +1,5 @@
+pub mod libtest_event;
+pub mod recorder;
+
+pub use libtest_event::LibtestEvent;
+pub use recorder::TestRunRecorder;
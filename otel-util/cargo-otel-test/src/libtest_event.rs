@@ -0,0 +1,59 @@
+use deps::serde::Deserialize;
+use deps::serde_json;
+
+// libtest の `--format json` (nextest の `--message-format libtest-json`) が 1 行ずつ出力するイベント。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "deps::serde")]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum LibtestEvent {
+    Suite(SuiteEvent),
+    Test(TestEvent),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(crate = "deps::serde")]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Started,
+    Ok,
+    Failed,
+    Ignored,
+    Timeout,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "deps::serde")]
+pub struct SuiteEvent {
+    pub event: EventKind,
+    pub test_count: Option<u64>,
+    pub passed: Option<u64>,
+    pub failed: Option<u64>,
+    pub ignored: Option<u64>,
+    pub filtered_out: Option<u64>,
+    pub exec_time: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "deps::serde")]
+pub struct TestEvent {
+    pub event: EventKind,
+    pub name: String,
+    pub exec_time: Option<f64>,
+    pub stdout: Option<String>,
+    pub message: Option<String>,
+}
+
+impl LibtestEvent {
+    // JSON でない行 (cargo や test 自体の出力) は None を返す。
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if !line.starts_with('{') {
+            return None;
+        }
+        serde_json::from_str(line).ok()
+    }
+}
@@ -0,0 +1,188 @@
+use cargo_otel_test::{LibtestEvent, TestRunRecorder};
+use cores::DefaultBatchOtelGuardFactory;
+use std::io::{BufRead, BufReader};
+use std::process::{exit, Command};
+
+const USAGE: &str = "\
+usage: cargo otel-test [--endpoint <url>] [--service-name <name>] [--nextest] [<cargo test args>...] [-- <test binary args>...]
+
+  --endpoint <url>       OTLP collector endpoint (default: grpc://localhost:4317)
+  --service-name <name>  service.name of the exported trace (default: cargo-otel-test)
+  --nextest              run `cargo nextest run` instead of `cargo test`";
+
+struct Args {
+    endpoint: String,
+    service_name: String,
+    nextest: bool,
+    cargo_args: Vec<String>,
+    test_args: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.peekable();
+        // `cargo otel-test` として呼ばれた場合、サブコマンド名が先頭に渡される。
+        args.next_if(|arg| arg == "otel-test");
+
+        let mut parsed = Args {
+            endpoint: "grpc://localhost:4317".to_string(),
+            service_name: "cargo-otel-test".to_string(),
+            nextest: false,
+            cargo_args: Vec::new(),
+            test_args: Vec::new(),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--endpoint" => {
+                    parsed.endpoint = args.next().ok_or("`--endpoint` requires a value")?;
+                }
+                "--service-name" => {
+                    parsed.service_name = args.next().ok_or("`--service-name` requires a value")?;
+                }
+                "--nextest" => parsed.nextest = true,
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                "--" => {
+                    parsed.test_args.extend(args.by_ref());
+                }
+                _ => parsed.cargo_args.push(arg),
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(std::env::var("CARGO").unwrap_or("cargo".to_string()));
+        if self.nextest {
+            command
+                .args(["nextest", "run", "--message-format", "libtest-json"])
+                .env("NEXTEST_EXPERIMENTAL_LIBTEST_JSON", "1")
+                .args(&self.cargo_args);
+            if !self.test_args.is_empty() {
+                command.arg("--").args(&self.test_args);
+            }
+        } else {
+            // stable の libtest で json 出力を使うには RUSTC_BOOTSTRAP が必要。
+            command
+                .arg("test")
+                .env("RUSTC_BOOTSTRAP", "1")
+                .args(&self.cargo_args)
+                .arg("--")
+                .args(&self.test_args)
+                .args([
+                    "-Z",
+                    "unstable-options",
+                    "--format",
+                    "json",
+                    "--report-time",
+                ]);
+        }
+        command
+    }
+}
+
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}");
+            exit(2);
+        }
+    };
+
+    // cargo の "Running unittests src/lib.rs (...)" (stderr) は、その suite の json (stdout) より先に書かれる。
+    // 同じ pipe から読むことで、suite の名前と json の順序を保つ。
+    let (reader, writer) = match std::io::pipe() {
+        Ok(pipe) => pipe,
+        Err(e) => {
+            eprintln!("failed to create a pipe: {e}");
+            exit(101);
+        }
+    };
+    let mut command = args.command();
+    command.stdout(writer.try_clone().expect("failed to clone the pipe"));
+    command.stderr(writer);
+    let command_line = format!("{command:?}");
+    let spawned = command.spawn();
+    // 書き込み側を閉じないと、cargo の終了後も EOF にならない。
+    drop(command);
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("failed to run {command_line}: {e}");
+            exit(101);
+        }
+    };
+
+    let guard = DefaultBatchOtelGuardFactory::new(
+        &args.endpoint,
+        &args.service_name,
+        env!("CARGO_PKG_VERSION"),
+    )
+    .build();
+    let mut recorder = TestRunRecorder::new(
+        if args.nextest {
+            "cargo nextest run"
+        } else {
+            "cargo test"
+        },
+        &command_line,
+    );
+
+    for line in BufReader::new(reader).lines().map_while(Result::ok) {
+        match LibtestEvent::parse(&line) {
+            Some(event) => {
+                print_event(&event);
+                recorder.record(event);
+            }
+            None => {
+                eprintln!("{line}");
+                if let Some(suite_name) = line.trim().strip_prefix("Running ") {
+                    recorder.set_next_suite_name(suite_name);
+                }
+            }
+        }
+    }
+
+    let status = child.wait().expect("failed to wait for cargo");
+    recorder.finish(status.success());
+    guard.force_flush();
+    // OTEL_TEST_EXPORT_ERROR=fail の場合は、テストが成功していても送信エラーで失敗とする。
+    if let Err(summary) = guard.check_export_errors() {
+        eprintln!("{summary}");
+        exit(status.code().filter(|code| *code != 0).unwrap_or(1));
+    }
+
+    exit(status.code().unwrap_or(101));
+}
+
+// json 出力では libtest の通常の表示が消えるため、同等の行を出す。
+fn print_event(event: &LibtestEvent) {
+    use cargo_otel_test::libtest_event::EventKind;
+
+    match event {
+        LibtestEvent::Test(test) => match test.event {
+            EventKind::Ok => println!("test {} ... ok", test.name),
+            EventKind::Ignored => println!("test {} ... ignored", test.name),
+            EventKind::Failed => {
+                println!("test {} ... FAILED", test.name);
+                if let Some(stdout) = &test.stdout {
+                    println!("---- {} stdout ----\n{stdout}", test.name);
+                }
+            }
+            _ => {}
+        },
+        LibtestEvent::Suite(suite) if suite.event != EventKind::Started => println!(
+            "test result: {}. {} passed; {} failed; {} ignored; {} filtered out",
+            if suite.event == EventKind::Ok {
+                "ok"
+            } else {
+                "FAILED"
+            },
+            suite.passed.unwrap_or_default(),
+            suite.failed.unwrap_or_default(),
+            suite.ignored.unwrap_or_default(),
+            suite.filtered_out.unwrap_or_default(),
+        ),
+        _ => {}
+    }
+}
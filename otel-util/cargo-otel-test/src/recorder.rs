@@ -0,0 +1,145 @@
+use crate::libtest_event::{EventKind, LibtestEvent, SuiteEvent, TestEvent};
+use deps::opentelemetry::global::{self, BoxedTracer};
+use deps::opentelemetry::trace::{Span, Status, TraceContextExt, Tracer};
+use deps::opentelemetry::{Context, KeyValue};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+// libtest のイベントから、run 全体の root span > suite span > test span の trace を組み立てる。
+pub struct TestRunRecorder {
+    tracer: BoxedTracer,
+    run_cx: Context,
+    suite_cx: Option<Context>,
+    suite_name: Option<String>,
+    started: HashMap<String, SystemTime>,
+    failed: u64,
+}
+
+impl TestRunRecorder {
+    pub fn new(run_name: &str, command: &str) -> Self {
+        let tracer = global::tracer("cargo-otel-test");
+        let run_span = tracer
+            .span_builder(run_name.to_string())
+            .with_attributes(vec![KeyValue::new("test.command", command.to_string())])
+            .start(&tracer);
+        TestRunRecorder {
+            tracer,
+            run_cx: Context::current_with_span(run_span),
+            suite_cx: None,
+            suite_name: None,
+            started: HashMap::new(),
+            failed: 0,
+        }
+    }
+
+    // 次に始まる suite の名前 (cargo の "Running unittests src/lib.rs (...)" など) を設定する。
+    pub fn set_next_suite_name(&mut self, name: &str) {
+        self.suite_name = Some(name.to_string());
+    }
+
+    pub fn record(&mut self, event: LibtestEvent) {
+        match event {
+            LibtestEvent::Suite(suite) => self.record_suite(suite),
+            LibtestEvent::Test(test) => self.record_test(test),
+            LibtestEvent::Other => {}
+        }
+    }
+
+    fn record_suite(&mut self, suite: SuiteEvent) {
+        match suite.event {
+            EventKind::Started => {
+                let name = self.suite_name.take().unwrap_or("test suite".to_string());
+                let mut attributes = vec![KeyValue::new("test.suite.name", name.clone())];
+                if let Some(test_count) = suite.test_count {
+                    attributes.push(KeyValue::new("test.suite.test_count", test_count as i64));
+                }
+                let span = self
+                    .tracer
+                    .span_builder(name)
+                    .with_attributes(attributes)
+                    .start_with_context(&self.tracer, &self.run_cx);
+                self.suite_cx = Some(self.run_cx.with_span(span));
+            }
+            EventKind::Ok | EventKind::Failed => {
+                if let Some(suite_cx) = self.suite_cx.take() {
+                    let span = suite_cx.span();
+                    for (key, value) in [
+                        ("test.suite.passed", suite.passed),
+                        ("test.suite.failed", suite.failed),
+                        ("test.suite.ignored", suite.ignored),
+                        ("test.suite.filtered_out", suite.filtered_out),
+                    ] {
+                        if let Some(value) = value {
+                            span.set_attribute(KeyValue::new(key, value as i64));
+                        }
+                    }
+                    if suite.event == EventKind::Failed {
+                        span.set_status(Status::error("test suite failed"));
+                    }
+                    span.end();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn record_test(&mut self, test: TestEvent) {
+        let outcome = match test.event {
+            EventKind::Started => {
+                self.started.insert(test.name, SystemTime::now());
+                return;
+            }
+            // 長時間実行中の通知のため、テストはまだ終わっていない。
+            EventKind::Timeout | EventKind::Other => return,
+            EventKind::Ok => "passed",
+            EventKind::Failed => "failed",
+            EventKind::Ignored => "ignored",
+        };
+
+        let end_time = SystemTime::now();
+        let start_time = match (self.started.remove(&test.name), test.exec_time) {
+            (_, Some(exec_time)) => end_time - Duration::from_secs_f64(exec_time),
+            (Some(start_time), None) => start_time,
+            (None, None) => end_time,
+        };
+
+        let parent_cx = self.suite_cx.as_ref().unwrap_or(&self.run_cx);
+        let mut span = self
+            .tracer
+            .span_builder(test.name.clone())
+            .with_start_time(start_time)
+            .with_attributes(vec![
+                KeyValue::new("test.name", test.name.clone()),
+                KeyValue::new("test.outcome", outcome),
+            ])
+            .start_with_context(&self.tracer, parent_cx);
+
+        if test.event == EventKind::Failed {
+            self.failed += 1;
+            let stdout = test.stdout.unwrap_or_default();
+            let message = test.message.unwrap_or("test failed".to_string());
+            span.add_event(
+                "exception",
+                vec![
+                    KeyValue::new("exception.message", message.clone()),
+                    KeyValue::new("test.stdout", stdout),
+                ],
+            );
+            span.set_status(Status::error(message));
+        }
+        span.end_with_timestamp(end_time);
+    }
+
+    // run 全体の root span を終了する。
+    pub fn finish(mut self, success: bool) {
+        if let Some(suite_cx) = self.suite_cx.take() {
+            suite_cx.span().end();
+        }
+        let span = self.run_cx.span();
+        span.set_attribute(KeyValue::new("test.failed", self.failed as i64));
+        if !success {
+            span.set_status(Status::error("cargo test failed"));
+        }
+        span.end();
+    }
+}
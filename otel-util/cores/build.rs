@@ -0,0 +1,21 @@
+use std::process::Command;
+
+// RustToolchainResourceDetector で使う、ビルド時の rustc のバージョンと profile を埋め込む。
+fn main() {
+    let rustc = std::env::var("RUSTC").unwrap_or("rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+    println!(
+        "cargo:rustc-env=OTEL_UTIL_RUSTC_VERSION={}",
+        rustc_version.trim()
+    );
+    println!(
+        "cargo:rustc-env=OTEL_UTIL_BUILD_PROFILE={}",
+        std::env::var("PROFILE").unwrap_or_default()
+    );
+    println!("cargo:rerun-if-changed=build.rs");
+}
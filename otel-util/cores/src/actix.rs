@@ -0,0 +1,89 @@
+use crate::{PropagationContext, WithTraceContext};
+use deps::actix_http::Request;
+use deps::actix_test::{self, TestServer};
+use deps::actix_web::body::MessageBody;
+use deps::actix_web::dev::{Service, ServiceFactory, ServiceRequest, ServiceResponse};
+use deps::actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use deps::actix_web::{test, App, Error};
+use deps::awc::ClientRequest;
+use deps::tracing;
+use deps::tracing_actix_web::TracingLogger;
+use deps::tracing_opentelemetry::OpenTelemetrySpanExt;
+
+fn current_context() -> PropagationContext {
+    PropagationContext::inject(&tracing::Span::current().context())
+}
+
+// TracingLogger は、リクエストのヘッダーに context がない場合に root span を作るため、
+// リクエストを処理する span (テストの span) の context を、既にヘッダーにあるものを除いて設定する。
+fn inject_current_context(headers: &mut HeaderMap) {
+    for (key, value) in current_context().0 {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            if !headers.contains_key(&name) {
+                headers.insert(name, value);
+            }
+        }
+    }
+}
+
+// TracingLogger を付与して test::init_service する。
+// test::call_service 等で送ったリクエストの span は、呼び出し元の span (テストの span) の子になる。
+pub async fn init_service<T, B>(
+    app: App<T>,
+) -> impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = Error>
+where
+    T: ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse<B>,
+            Error = Error,
+            InitError = (),
+        > + 'static,
+    B: MessageBody + 'static,
+{
+    let app = app
+        .wrap(TracingLogger::default())
+        .wrap_fn(|mut req, service| {
+            inject_current_context(req.headers_mut());
+            service.call(req)
+        });
+    test::init_service(app).await
+}
+
+// TracingLogger を付与して actix_test::start する。
+// サーバーは別スレッドで動くため、リクエストには with_trace_context() で context を設定すること。
+pub fn start<F, T, B>(factory: F) -> TestServer
+where
+    F: Fn() -> App<T> + Send + Clone + 'static,
+    T: ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse<B>,
+            Error = Error,
+            InitError = (),
+        > + 'static,
+    B: MessageBody + 'static,
+{
+    actix_test::start(move || factory().wrap(TracingLogger::default()))
+}
+
+impl WithTraceContext for ClientRequest {
+    fn with_trace_context(self) -> Self {
+        current_context()
+            .0
+            .into_iter()
+            .fold(self, |request, header| request.insert_header(header))
+    }
+}
+
+impl WithTraceContext for test::TestRequest {
+    fn with_trace_context(self) -> Self {
+        current_context()
+            .0
+            .into_iter()
+            .fold(self, |request, header| request.insert_header(header))
+    }
+}
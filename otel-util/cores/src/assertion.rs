@@ -0,0 +1,224 @@
+use deps::tokio::task::futures::TaskLocalFuture;
+use deps::tracing;
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::panic::Location;
+use std::sync::{Arc, Mutex};
+
+// otel_assert! 系のマクロで失敗した assert の内容。
+// otel_assert_eq!(actual, expected) のように、左辺を実際の値、右辺を期待値として記録する。
+#[derive(Debug, Clone)]
+pub struct AssertionFailure {
+    pub kind: &'static str,
+    pub expression: &'static str,
+    pub expected: String,
+    pub actual: String,
+    pub message: Option<String>,
+}
+
+impl fmt::Display for AssertionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "assertion `{}` failed", self.expression)?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        write!(
+            f,
+            "\n  expected: {}\n    actual: {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+// テスト 1 つ分の、soft mode で記録した失敗。use_otel_at_test がテストごとに作り、テストの本体を scope の中で実行する。
+// trace ではなくテストの実行単位で集めるため、parent: None の span や link した span の中で失敗しても集計される。
+#[derive(Debug, Clone, Default)]
+pub struct SoftAssertions(Arc<Mutex<Vec<String>>>);
+
+deps::tokio::task_local! {
+    static TASK_SOFT_ASSERTIONS: SoftAssertions;
+}
+
+thread_local! {
+    static THREAD_SOFT_ASSERTIONS: RefCell<Option<SoftAssertions>> = const { RefCell::new(None) };
+}
+
+impl SoftAssertions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 現在の task またはスレッドで有効な集計先。
+    pub(crate) fn current() -> Option<Self> {
+        TASK_SOFT_ASSERTIONS
+            .try_with(Clone::clone)
+            .ok()
+            .or_else(|| THREAD_SOFT_ASSERTIONS.with(|current| current.borrow().clone()))
+    }
+
+    // f の実行中 (このスレッド上) の soft mode の失敗を、この集計先に記録する。
+    pub fn in_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        struct Restore(Option<SoftAssertions>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                THREAD_SOFT_ASSERTIONS.with(|current| *current.borrow_mut() = self.0.take());
+            }
+        }
+        let _restore = Restore(
+            THREAD_SOFT_ASSERTIONS.with(|current| current.borrow_mut().replace(self.clone())),
+        );
+        f()
+    }
+
+    // future の実行中 (どのスレッドで poll されても) の soft mode の失敗を、この集計先に記録する。
+    pub fn scope<F: Future>(&self, future: F) -> TaskLocalFuture<SoftAssertions, F> {
+        TASK_SOFT_ASSERTIONS.scope(self.clone(), future)
+    }
+
+    // spawn したスレッド等で、spawn 元の集計先を引き継ぐ。集計先がなければ f をそのまま実行する。
+    pub(crate) fn in_optional_scope<R>(soft_assertions: Option<&Self>, f: impl FnOnce() -> R) -> R {
+        match soft_assertions {
+            Some(soft_assertions) => soft_assertions.in_scope(f),
+            None => f(),
+        }
+    }
+
+    fn push(&self, failure: String) {
+        self.0.lock().unwrap().push(failure);
+    }
+
+    // 記録した失敗があれば、その一覧を返す。use_otel_at_test はテストの終了時にこれを呼び、失敗があればテストを失敗させる。
+    pub fn take_summary(&self) -> Option<String> {
+        let failures = std::mem::take(&mut *self.0.lock().unwrap());
+        if failures.is_empty() {
+            return None;
+        }
+        let mut summary = format!("{} soft assertion(s) failed", failures.len());
+        for failure in failures {
+            summary.push_str(&format!("\n- {}", failure));
+        }
+        Some(summary)
+    }
+}
+
+// 現在の span に assertion event を記録し (span の status は Error になる)、soft でなければ panic する。
+// soft でも、テストの scope の外 (集計先がない) の場合は、失敗を見落とさないよう panic する。
+#[track_caller]
+pub fn assertion_failed(failure: AssertionFailure, soft: bool) {
+    let location = Location::caller();
+    tracing::error!(
+        assertion.kind = failure.kind,
+        assertion.expression = failure.expression,
+        assertion.expected = failure.expected.as_str(),
+        assertion.actual = failure.actual.as_str(),
+        assertion.message = failure.message.as_deref(),
+        assertion.soft = soft,
+        // code.filepath 等は tracing-opentelemetry が event の発生箇所 (この関数) で埋めるため、別の名前で記録する。
+        assertion.location = %location,
+        "assertion"
+    );
+    match SoftAssertions::current().filter(|_| soft) {
+        Some(soft_assertions) => soft_assertions.push(format!("{}: {}", location, failure)),
+        None => panic!("{}", failure),
+    }
+}
+
+// assert! と同じ形で使える。otel_assert!(soft; cond) の場合は、失敗を記録してテストの終了まで panic しない。
+#[macro_export]
+macro_rules! otel_assert {
+    (soft; $cond:expr $(,)?) => {
+        $crate::__otel_assert!(true, $cond, ::std::option::Option::None)
+    };
+    (soft; $cond:expr, $($arg:tt)+) => {
+        $crate::__otel_assert!(true, $cond, ::std::option::Option::Some(::std::format!($($arg)+)))
+    };
+    ($cond:expr $(,)?) => {
+        $crate::__otel_assert!(false, $cond, ::std::option::Option::None)
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        $crate::__otel_assert!(false, $cond, ::std::option::Option::Some(::std::format!($($arg)+)))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __otel_assert {
+    ($soft:expr, $cond:expr, $message:expr) => {
+        if !$cond {
+            $crate::assertion_failed(
+                $crate::AssertionFailure {
+                    kind: "assert",
+                    expression: ::std::stringify!($cond),
+                    expected: ::std::string::String::from("true"),
+                    actual: ::std::string::String::from("false"),
+                    message: $message,
+                },
+                $soft,
+            );
+        }
+    };
+}
+
+// assert_eq! と同じ形で使える。左辺を実際の値、右辺を期待値として記録する。
+#[macro_export]
+macro_rules! otel_assert_eq {
+    (soft; $actual:expr, $expected:expr $(,)?) => {
+        $crate::__otel_assert_cmp!(true, ==, "assert_eq", "", $actual, $expected, ::std::option::Option::None)
+    };
+    (soft; $actual:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::__otel_assert_cmp!(true, ==, "assert_eq", "", $actual, $expected, ::std::option::Option::Some(::std::format!($($arg)+)))
+    };
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::__otel_assert_cmp!(false, ==, "assert_eq", "", $actual, $expected, ::std::option::Option::None)
+    };
+    ($actual:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::__otel_assert_cmp!(false, ==, "assert_eq", "", $actual, $expected, ::std::option::Option::Some(::std::format!($($arg)+)))
+    };
+}
+
+// assert_ne! と同じ形で使える。期待値は "!= <右辺>" として記録する。
+#[macro_export]
+macro_rules! otel_assert_ne {
+    (soft; $actual:expr, $expected:expr $(,)?) => {
+        $crate::__otel_assert_cmp!(true, !=, "assert_ne", "!= ", $actual, $expected, ::std::option::Option::None)
+    };
+    (soft; $actual:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::__otel_assert_cmp!(true, !=, "assert_ne", "!= ", $actual, $expected, ::std::option::Option::Some(::std::format!($($arg)+)))
+    };
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::__otel_assert_cmp!(false, !=, "assert_ne", "!= ", $actual, $expected, ::std::option::Option::None)
+    };
+    ($actual:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::__otel_assert_cmp!(false, !=, "assert_ne", "!= ", $actual, $expected, ::std::option::Option::Some(::std::format!($($arg)+)))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __otel_assert_cmp {
+    ($soft:expr, $op:tt, $kind:expr, $expected_prefix:expr, $actual:expr, $expected:expr, $message:expr) => {
+        match (&$actual, &$expected) {
+            (actual, expected) => {
+                if !(*actual $op *expected) {
+                    $crate::assertion_failed(
+                        $crate::AssertionFailure {
+                            kind: $kind,
+                            expression: ::std::concat!(
+                                ::std::stringify!($actual),
+                                " ",
+                                ::std::stringify!($op),
+                                " ",
+                                ::std::stringify!($expected)
+                            ),
+                            expected: ::std::format!("{}{:?}", $expected_prefix, expected),
+                            actual: ::std::format!("{:?}", actual),
+                            message: $message,
+                        },
+                        $soft,
+                    );
+                }
+            }
+        }
+    };
+}
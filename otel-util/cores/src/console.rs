@@ -0,0 +1,321 @@
+use crate::test_settings::current_test_settings;
+use deps::opentelemetry::trace::{SpanId, TraceContextExt, TraceId};
+use deps::serde_json::{json, Map, Value};
+use deps::tracing_core::field::{Field, Visit};
+use deps::tracing_core::span::{Attributes, Id};
+use deps::tracing_core::subscriber::Interest;
+use deps::tracing_core::{Event, Level, Metadata, Subscriber};
+use deps::tracing_opentelemetry::OtelData;
+use deps::tracing_subscriber::fmt;
+use deps::tracing_subscriber::layer::{self, Filter, Layer};
+use deps::tracing_subscriber::registry::{LookupSpan, SpanRef};
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+// 標準出力への出力形式。名前は OTEL_TEST_CONSOLE 環境変数の値と同じ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConsoleOutput {
+    // 出力しない。
+    Off,
+    // tracing_subscriber::fmt の既定の形式。
+    #[default]
+    Full,
+    // tracing_subscriber::fmt の compact 形式。
+    Compact,
+    // 1 行 1 event の JSON。trace_id / span_id を含むため、ログと trace を突き合わせられる。
+    Json,
+    // テストの終了時 (root span の終了時) に、span の木を所要時間と status 付きで出力する。
+    Tree,
+}
+
+impl FromStr for ConsoleOutput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "off" => Ok(ConsoleOutput::Off),
+            "full" => Ok(ConsoleOutput::Full),
+            "compact" => Ok(ConsoleOutput::Compact),
+            "json" => Ok(ConsoleOutput::Json),
+            "tree" => Ok(ConsoleOutput::Tree),
+            other => Err(format!(
+                "unknown console output `{}`; expected `off`, `full`, `compact`, `json` or `tree`",
+                other
+            )),
+        }
+    }
+}
+
+impl ConsoleOutput {
+    // OTEL_TEST_CONSOLE が指定されている場合は、コード上の指定より優先する。
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("OTEL_TEST_CONSOLE").ok()?;
+        value
+            .parse()
+            .map_err(|e| println!("OTEL_TEST_CONSOLE is ignored: {}", e))
+            .ok()
+    }
+
+    const ALL: [ConsoleOutput; 5] = [
+        ConsoleOutput::Off,
+        ConsoleOutput::Full,
+        ConsoleOutput::Compact,
+        ConsoleOutput::Json,
+        ConsoleOutput::Tree,
+    ];
+}
+
+// subscriber は 1 プロセスで 1 回だけ登録されるため、すべての形式の layer を登録しておき、現在のテストの形式の layer だけを有効にする。
+// テストの root span の外 (parent のない span 等) には、最後に初期化した guard の形式を適用する。
+static CONSOLE_OUTPUT: AtomicU8 = AtomicU8::new(ConsoleOutput::Full as u8);
+
+pub(crate) fn set_console_output(output: ConsoleOutput) {
+    CONSOLE_OUTPUT.store(output as u8, Ordering::SeqCst);
+}
+
+fn console_output() -> ConsoleOutput {
+    current_test_settings().map_or_else(
+        || ConsoleOutput::ALL[CONSOLE_OUTPUT.load(Ordering::SeqCst) as usize],
+        |settings| settings.console_output,
+    )
+}
+
+pub(crate) fn console_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fmt::layer()
+        .with_filter(OutputFilter(ConsoleOutput::Full))
+        .and_then(
+            fmt::layer()
+                .compact()
+                .with_filter(OutputFilter(ConsoleOutput::Compact)),
+        )
+        .and_then(JsonLayer.with_filter(OutputFilter(ConsoleOutput::Json)))
+        .and_then(SpanTreeLayer.with_filter(OutputFilter(ConsoleOutput::Tree)))
+}
+
+// 形式はテストごとに変わるため、callsite ごとの判定を cache させない。
+struct OutputFilter(ConsoleOutput);
+
+impl<S> Filter<S> for OutputFilter {
+    fn enabled(&self, _metadata: &Metadata<'_>, _ctx: &layer::Context<'_, S>) -> bool {
+        console_output() == self.0
+    }
+
+    fn callsite_enabled(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        Interest::sometimes()
+    }
+}
+
+// otel.name が指定されている場合は、OTel と同じくそれを span 名とする。
+struct SpanName(String);
+
+fn record_span_name<S>(attrs: &Attributes<'_>, span: &SpanRef<'_, S>)
+where
+    S: for<'span> LookupSpan<'span>,
+{
+    let mut visitor = FieldVisitor::default();
+    attrs.record(&mut visitor);
+    let name = match visitor.fields.remove("otel.name") {
+        Some(Value::String(name)) => name,
+        _ => span.name().to_string(),
+    };
+    span.extensions_mut().insert(SpanName(name));
+}
+
+fn span_name<S>(span: &SpanRef<'_, S>) -> String
+where
+    S: for<'span> LookupSpan<'span>,
+{
+    span.extensions()
+        .get::<SpanName>()
+        .map_or_else(|| span.name().to_string(), |name| name.0.clone())
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    fields: Map<String, Value>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.fields
+            .insert(field.name().to_string(), json!(format!("{:?}", value)));
+    }
+}
+
+// span (または OTel layer の filter で除外された span の場合は、その祖先) の OTel の trace ID と span ID。
+fn otel_ids<S>(span: &SpanRef<'_, S>) -> Option<(TraceId, SpanId)>
+where
+    S: for<'span> LookupSpan<'span>,
+{
+    span.scope().find_map(|span| {
+        let extensions = span.extensions();
+        let otel_data = extensions.get::<OtelData>()?;
+        let span_id = otel_data.builder.span_id?;
+        let trace_id = otel_data
+            .builder
+            .trace_id
+            .unwrap_or_else(|| otel_data.parent_cx.span().span_context().trace_id());
+        Some((trace_id, span_id))
+    })
+}
+
+struct JsonLayer;
+
+impl<S> Layer<S> for JsonLayer
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: layer::Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            record_span_name(attrs, &span);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: layer::Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let message = visitor.fields.remove("message").unwrap_or(Value::Null);
+        let metadata = event.metadata();
+        let mut line = json!({
+            "timestamp_unix_nano": UNIX_EPOCH.elapsed().map_or(0, |duration| duration.as_nanos() as u64),
+            "level": metadata.level().as_str(),
+            "target": metadata.target(),
+            "message": message,
+            "fields": visitor.fields,
+        });
+        if let Some(span) = ctx.event_span(event) {
+            line["span"] = json!(span_name(&span));
+            if let Some((trace_id, span_id)) = otel_ids(&span) {
+                line["trace_id"] = json!(trace_id.to_string());
+                line["span_id"] = json!(span_id.to_string());
+            }
+        }
+        let _ = writeln!(std::io::stdout().lock(), "{}", line);
+    }
+}
+
+// span の木の 1 節。子 span は終了した順に追加される。
+struct TreeNode {
+    name: String,
+    start: Instant,
+    failed: bool,
+    children: Vec<FinishedNode>,
+}
+
+struct FinishedNode {
+    name: String,
+    duration: Duration,
+    failed: bool,
+    children: Vec<FinishedNode>,
+}
+
+impl FinishedNode {
+    fn write(&self, out: &mut String, prefix: &str, child_prefix: &str) {
+        out.push_str(&format!(
+            "{}{} ({:.2?}) {}\n",
+            prefix,
+            self.name,
+            self.duration,
+            if self.failed { "error" } else { "ok" }
+        ));
+        for (i, child) in self.children.iter().enumerate() {
+            let (branch, next) = if i + 1 == self.children.len() {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            child.write(
+                out,
+                &format!("{}{}", child_prefix, branch),
+                &format!("{}{}", child_prefix, next),
+            );
+        }
+    }
+}
+
+// ERROR の event が記録された span を error とする (tracing-opentelemetry が status を Error にするのと同じ条件)。
+// 子孫の span が error の場合も error とし、テストの中のどこかで失敗した場合は root span も error として出力する。
+struct SpanTreeLayer;
+
+impl<S> Layer<S> for SpanTreeLayer
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: layer::Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        record_span_name(attrs, &span);
+        let name = span_name(&span);
+        span.extensions_mut().insert(TreeNode {
+            name,
+            start: Instant::now(),
+            failed: false,
+            children: Vec::new(),
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: layer::Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        if let Some(span) = ctx.event_span(event) {
+            if let Some(node) = span.extensions_mut().get_mut::<TreeNode>() {
+                node.failed = true;
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: layer::Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(node) = span.extensions_mut().remove::<TreeNode>() else {
+            return;
+        };
+        let finished = FinishedNode {
+            name: node.name,
+            duration: node.start.elapsed(),
+            failed: node.failed,
+            children: node.children,
+        };
+        match span.parent() {
+            Some(parent) => {
+                if let Some(parent_node) = parent.extensions_mut().get_mut::<TreeNode>() {
+                    parent_node.failed |= finished.failed;
+                    parent_node.children.push(finished);
+                }
+            }
+            None => {
+                let mut out = String::new();
+                finished.write(&mut out, "", "");
+                let _ = std::io::stdout().lock().write_all(out.as_bytes());
+            }
+        }
+    }
+}
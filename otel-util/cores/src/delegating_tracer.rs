@@ -0,0 +1,135 @@
+use crate::test_settings::{current_test_settings, latest_guard_settings, test_settings_of};
+use deps::opentelemetry::global;
+use deps::opentelemetry::trace::{SpanBuilder, SpanId, TraceId, TracerProvider as _};
+use deps::opentelemetry::{Context, InstrumentationLibrary};
+use deps::opentelemetry_sdk::trace::{Span, Tracer, TracerProvider};
+use deps::opentelemetry_sdk::Resource;
+use deps::tracing_core::span::{Attributes, Id};
+use deps::tracing_core::Subscriber;
+use deps::tracing_opentelemetry::{OtelData, PreSampledTracer};
+use deps::tracing_subscriber::layer::{self, Layer};
+use deps::tracing_subscriber::registry::LookupSpan;
+use std::cell::RefCell;
+use std::sync::{Arc, Once, OnceLock};
+
+// subscriber と global の tracer provider はプロセスで 1 つだが、tracer provider は guard ごとに作る。
+// span の送信を、その span が属するテストの guard の tracer に委譲し、連続・並行して実行されるテストが、
+// 互いの exporter や送信の設定、guard の drop 時の終了処理に影響しないようにする。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DelegatingTracer {
+    // global::tracer 等、global の tracer provider から作った場合の instrumentation library。
+    library: Option<Arc<InstrumentationLibrary>>,
+}
+
+thread_local! {
+    // TracerSelectLayer が、終了する span の tracer を OpenTelemetryLayer に渡すためのもの。
+    // OpenTelemetryLayer は span の extension を書き込み中に tracer を呼ぶため、tracer からは span をたどれない。
+    static CLOSING_SPAN_TRACER: RefCell<Option<Tracer>> = const { RefCell::new(None) };
+}
+
+// span processor を持たない tracer。送信先の guard がない span は、これで作って捨てる。
+// sampler と ID の生成方法はすべての guard で同じため、trace ID の生成と sampling の判定にも使う。
+fn idle_tracer() -> &'static Tracer {
+    static IDLE_TRACER: OnceLock<(TracerProvider, Tracer)> = OnceLock::new();
+    let (_, tracer) = IDLE_TRACER.get_or_init(|| {
+        let provider = TracerProvider::builder()
+            .with_config(crate::trace_config(Resource::empty()))
+            .build();
+        let tracer = provider.tracer("otel-util");
+        (provider, tracer)
+    });
+    tracer
+}
+
+// テストの外の span は、drop されていない guard のうち、最後に初期化したものの tracer で送信する。
+fn default_tracer() -> Tracer {
+    latest_guard_settings()
+        .map(|settings| settings.tracer.clone())
+        .unwrap_or_else(|| idle_tracer().clone())
+}
+
+impl deps::opentelemetry::trace::Tracer for DelegatingTracer {
+    type Span = Span;
+
+    fn build_with_context(&self, builder: SpanBuilder, parent_cx: &Context) -> Self::Span {
+        let tracer = CLOSING_SPAN_TRACER
+            .with(|closing| closing.borrow_mut().take())
+            .or_else(|| current_test_settings().map(|settings| settings.tracer.clone()))
+            .unwrap_or_else(default_tracer);
+        match (&self.library, tracer.provider()) {
+            (Some(library), Some(provider)) => provider
+                .library_tracer(library.clone())
+                .build_with_context(builder, parent_cx),
+            _ => tracer.build_with_context(builder, parent_cx),
+        }
+    }
+}
+
+impl PreSampledTracer for DelegatingTracer {
+    fn sampled_context(&self, data: &mut OtelData) -> Context {
+        idle_tracer().sampled_context(data)
+    }
+
+    fn new_trace_id(&self) -> TraceId {
+        idle_tracer().new_trace_id()
+    }
+
+    fn new_span_id(&self) -> SpanId {
+        idle_tracer().new_span_id()
+    }
+}
+
+// global::tracer 等で作った span も、現在の span が属するテストの guard の tracer で送信する。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DelegatingTracerProvider;
+
+impl deps::opentelemetry::trace::TracerProvider for DelegatingTracerProvider {
+    type Tracer = DelegatingTracer;
+
+    fn library_tracer(&self, library: Arc<InstrumentationLibrary>) -> Self::Tracer {
+        DelegatingTracer {
+            library: Some(library),
+        }
+    }
+}
+
+// global の tracer provider は guard ごとに差し替えず、1 度だけ設定する。
+pub(crate) fn install_global_tracer_provider() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        global::set_tracer_provider(DelegatingTracerProvider);
+    });
+}
+
+// span を作った時点で決めた、その span を送信する tracer。
+struct SpanTracer(Tracer);
+
+// OpenTelemetryLayer より前に置き、span を作った時点の tracer を、終了時に DelegatingTracer へ渡す。
+pub(crate) struct TracerSelectLayer;
+
+impl<S> Layer<S> for TracerSelectLayer
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: layer::Context<'_, S>) {
+        let tracer = match test_settings_of(id) {
+            Some(settings) => settings.tracer.clone(),
+            None => default_tracer(),
+        };
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanTracer(tracer));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: layer::Context<'_, S>) {
+        let tracer = ctx
+            .span(&id)
+            .and_then(|span| {
+                span.extensions()
+                    .get::<SpanTracer>()
+                    .map(|tracer| tracer.0.clone())
+            })
+            .unwrap_or_else(default_tracer);
+        CLOSING_SPAN_TRACER.with(|closing| *closing.borrow_mut() = Some(tracer));
+    }
+}
@@ -0,0 +1,95 @@
+use deps::opentelemetry::trace::TraceContextExt;
+use deps::tracing_core::callsite::Identifier;
+use deps::tracing_core::span::{Attributes, Id};
+use deps::tracing_core::Subscriber;
+use deps::tracing_opentelemetry::OtelData;
+use deps::tracing_subscriber::registry::LookupSpan;
+use deps::tracing_subscriber::{layer, Layer};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// テストの root span (test.name を持つ root span) の実行中に、親を持たない span が作られた場合に警告する。
+// tokio::spawn や std::thread::spawn で span が引き継がれず、別の trace として送信されてしまうのを見つけるためのもの。
+#[derive(Default)]
+pub(crate) struct OrphanSpanLayer {
+    active_test_roots: AtomicUsize,
+    warned_callsites: Mutex<HashSet<Identifier>>,
+}
+
+// テストの root span であることを示す extension。
+struct TestRoot;
+
+// 親の確認済みであることを示す extension。
+struct ParentChecked;
+
+impl<S> Layer<S> for OrphanSpanLayer
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: layer::Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if span.parent().is_none() && attrs.metadata().fields().field("test.name").is_some() {
+            self.active_test_roots.fetch_add(1, Ordering::SeqCst);
+            span.extensions_mut().insert(TestRoot);
+        }
+    }
+
+    // span 作成後に set_parent や add_link で親が設定される場合があるため、最初に enter された時点で確認する。
+    fn on_enter(&self, id: &Id, ctx: layer::Context<'_, S>) {
+        if self.active_test_roots.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if span.parent().is_some() {
+            return;
+        }
+        let mut extensions = span.extensions_mut();
+        if extensions.get_mut::<TestRoot>().is_some()
+            || extensions.get_mut::<ParentChecked>().is_some()
+        {
+            return;
+        }
+        extensions.insert(ParentChecked);
+        let has_otel_parent = extensions.get_mut::<OtelData>().is_some_and(|otel_data| {
+            otel_data.parent_cx.has_active_span()
+                || otel_data
+                    .builder
+                    .links
+                    .as_ref()
+                    .is_some_and(|links| !links.is_empty())
+        });
+        if has_otel_parent {
+            return;
+        }
+
+        let metadata = span.metadata();
+        if self
+            .warned_callsites
+            .lock()
+            .unwrap()
+            .insert(metadata.callsite())
+        {
+            eprintln!(
+                "otel-util: span `{}` ({}:{}) was created without a parent while a test is running, so it is exported as a separate trace. \
+                 Use otel_util::spawn, spawn_blocking, thread::spawn or rayon::scope to keep the test's span.",
+                metadata.name(),
+                metadata.file().unwrap_or("unknown"),
+                metadata.line().unwrap_or(0),
+            );
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: layer::Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        if span.extensions().get::<TestRoot>().is_some() {
+            self.active_test_roots.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use deps::futures::executor::block_on;
+use deps::opentelemetry::trace::{SpanId, Status, TraceError, TraceId, TraceResult};
+use deps::opentelemetry::{global, Context};
+use deps::opentelemetry_sdk::export::trace::{SpanData, SpanExporter};
+use deps::opentelemetry_sdk::trace::{Span, SpanProcessor};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{mpsc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// テストの trace をいつ送信するか。名前は OTEL_TEST_EXPORT 環境変数の値と同じ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportMode {
+    // すべての span を終了時に送信する。
+    #[default]
+    Always,
+    // trace の span をメモリに溜めておき、テストが失敗した場合 (または遅かった場合) にだけ送信する。
+    OnFailure,
+}
+
+impl FromStr for ExportMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "always" => Ok(ExportMode::Always),
+            "on_failure" => Ok(ExportMode::OnFailure),
+            other => Err(format!(
+                "unknown export mode `{}`; expected `always` or `on_failure`",
+                other
+            )),
+        }
+    }
+}
+
+impl ExportMode {
+    // OTEL_TEST_EXPORT が指定されている場合は、コード上の指定より優先する。
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("OTEL_TEST_EXPORT").ok()?;
+        value
+            .parse()
+            .map_err(|e| println!("OTEL_TEST_EXPORT is ignored: {}", e))
+            .ok()
+    }
+}
+
+// OTEL_TEST_SLOW_THRESHOLD_MS が指定されている場合は、コード上の指定より優先する。
+pub(crate) fn slow_threshold_from_env() -> Option<Duration> {
+    let value = std::env::var("OTEL_TEST_SLOW_THRESHOLD_MS").ok()?;
+    value
+        .trim()
+        .parse()
+        .map(Duration::from_millis)
+        .map_err(|e| println!("OTEL_TEST_SLOW_THRESHOLD_MS is ignored: {}", e))
+        .ok()
+}
+
+enum Message {
+    Export(Vec<SpanData>),
+    Flush(mpsc::SyncSender<()>),
+    Shutdown(mpsc::SyncSender<()>),
+}
+
+impl std::fmt::Debug for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Message::Export(spans) => write!(f, "Export({} spans)", spans.len()),
+            Message::Flush(_) => write!(f, "Flush"),
+            Message::Shutdown(_) => write!(f, "Shutdown"),
+        }
+    }
+}
+
+// trace ごとに終了した span を溜め、trace の root (またはテストの root span) が終了した時点で送信するかを決める。
+// いずれかの span が Error (panic, Err の unwrap, error! 等) の場合と、root span が slow_threshold より長くかかった場合に送信する。
+// 子プロセス等、root がこのプロセスにない trace は、shutdown 時に同じ条件で判定する。
+#[derive(Debug)]
+pub(crate) struct OnFailureSpanProcessor {
+    traces: Mutex<HashMap<TraceId, Vec<SpanData>>>,
+    slow_threshold: Option<Duration>,
+    sender: mpsc::Sender<Message>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl OnFailureSpanProcessor {
+    pub(crate) fn new(
+        mut exporter: Box<dyn SpanExporter>,
+        slow_threshold: Option<Duration>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let worker = thread::Builder::new()
+            .name("otel-util-on-failure-exporter".to_string())
+            .spawn(move || {
+                while let Ok(message) = receiver.recv() {
+                    match message {
+                        Message::Export(spans) => {
+                            if let Err(err) = block_on(exporter.export(spans)) {
+                                global::handle_error(err);
+                            }
+                        }
+                        Message::Flush(done) => {
+                            let _ = done.send(());
+                        }
+                        Message::Shutdown(done) => {
+                            exporter.shutdown();
+                            let _ = done.send(());
+                            break;
+                        }
+                    }
+                }
+            })
+            .expect("Failed to spawn the exporter thread");
+        Self {
+            traces: Mutex::new(HashMap::new()),
+            slow_threshold,
+            sender,
+            worker: Some(worker),
+        }
+    }
+
+    fn is_decision_point(span: &SpanData) -> bool {
+        span.parent_span_id == SpanId::INVALID
+            || span
+                .attributes
+                .iter()
+                .any(|attribute| attribute.key.as_str() == "test.name")
+    }
+
+    fn should_export(&self, spans: &[SpanData], root: Option<&SpanData>) -> bool {
+        let failed = spans
+            .iter()
+            .any(|span| matches!(span.status, Status::Error { .. }));
+        let slow = match (self.slow_threshold, root) {
+            (Some(threshold), Some(root)) => root
+                .end_time
+                .duration_since(root.start_time)
+                .is_ok_and(|duration| duration > threshold),
+            _ => false,
+        };
+        failed || slow
+    }
+
+    fn send(&self, message: Message) -> TraceResult<()> {
+        self.sender
+            .send(message)
+            .map_err(|_| TraceError::Other("the exporter thread has already stopped".into()))
+    }
+
+    fn request(&self, message: impl FnOnce(mpsc::SyncSender<()>) -> Message) -> TraceResult<()> {
+        let (done, wait) = mpsc::sync_channel(1);
+        self.send(message(done))?;
+        wait.recv()
+            .map_err(|_| TraceError::Other("the exporter thread has already stopped".into()))
+    }
+}
+
+impl SpanProcessor for OnFailureSpanProcessor {
+    fn on_start(&self, _span: &mut Span, _cx: &Context) {}
+
+    fn on_end(&self, span: SpanData) {
+        if !span.span_context.is_sampled() {
+            return;
+        }
+        let trace_id = span.span_context.trace_id();
+        let is_decision_point = Self::is_decision_point(&span);
+        let mut traces = self.traces.lock().unwrap();
+        traces.entry(trace_id).or_default().push(span);
+        if !is_decision_point {
+            return;
+        }
+
+        let spans = traces.remove(&trace_id).unwrap_or_default();
+        drop(traces);
+        if self.should_export(&spans, spans.last()) {
+            if let Err(err) = self.send(Message::Export(spans)) {
+                global::handle_error(err);
+            }
+        }
+    }
+
+    // 判定済みの trace の送信完了を待つ。判定前の trace は送信しない。
+    fn force_flush(&self) -> TraceResult<()> {
+        self.request(Message::Flush)
+    }
+
+    fn shutdown(&mut self) -> TraceResult<()> {
+        let remaining: Vec<Vec<SpanData>> = self
+            .traces
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(_, spans)| spans)
+            .collect();
+        for spans in remaining {
+            if self.should_export(&spans, None) {
+                self.send(Message::Export(spans))?;
+            }
+        }
+        self.request(Message::Shutdown)?;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use deps::futures::future::{self, BoxFuture};
+use deps::futures::FutureExt;
+use deps::opentelemetry::global::{self, Error};
+use deps::opentelemetry::trace::TraceError;
+use deps::opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, Once};
+
+// trace の送信に失敗した場合に、テストをどう扱うか。名前は OTEL_TEST_EXPORT_ERROR 環境変数の値と同じ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportErrorPolicy {
+    // 何も出力しない。
+    Ignore,
+    // テストの終了時に、送信エラーの集計を標準エラーに出力する。
+    #[default]
+    Warn,
+    // 送信エラーがあった場合、集計を panic のメッセージとしてテストを失敗させる。
+    Fail,
+}
+
+impl FromStr for ExportErrorPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "ignore" => Ok(ExportErrorPolicy::Ignore),
+            "warn" => Ok(ExportErrorPolicy::Warn),
+            "fail" => Ok(ExportErrorPolicy::Fail),
+            other => Err(format!(
+                "unknown export error policy `{}`; expected `ignore`, `warn` or `fail`",
+                other
+            )),
+        }
+    }
+}
+
+impl ExportErrorPolicy {
+    // OTEL_TEST_EXPORT_ERROR が指定されている場合は、コード上の指定より優先する。
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("OTEL_TEST_EXPORT_ERROR").ok()?;
+        value
+            .parse()
+            .map_err(|e| println!("OTEL_TEST_EXPORT_ERROR is ignored: {}", e))
+            .ok()
+    }
+}
+
+// 前回の集計以降に発生した送信エラー。同じメッセージのエラーはまとめて数える。
+#[derive(Debug, Default)]
+struct ExportErrors {
+    endpoint: Option<String>,
+    dropped_spans: usize,
+    errors: Vec<(String, usize)>,
+}
+
+// exporter ごとの送信エラーの集計。exporter と、その exporter で送信する guard が共有する。
+// 並列に実行されるテストのエラーが、別のテストの失敗として報告されないよう、プロセス全体では集計しない。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExportErrorCounter(Arc<Mutex<ExportErrors>>);
+
+impl ExportErrorCounter {
+    fn record(&self, endpoint: &str, span_count: usize, message: String) {
+        let mut export_errors = self.0.lock().unwrap();
+        export_errors.endpoint = Some(endpoint.to_string());
+        export_errors.dropped_spans += span_count;
+        match export_errors.errors.iter_mut().find(|(m, _)| *m == message) {
+            Some((_, count)) => *count += 1,
+            None => export_errors.errors.push((message, 1)),
+        }
+    }
+
+    // 前回の呼び出し以降の送信エラーの集計を取り出す。エラーがなければ None。
+    pub(crate) fn take_summary(&self) -> Option<String> {
+        let export_errors = std::mem::take(&mut *self.0.lock().unwrap());
+        if export_errors.errors.is_empty() && export_errors.dropped_spans == 0 {
+            return None;
+        }
+        let mut summary = format!(
+            "otel-util: failed to export {} span(s) to {}",
+            export_errors.dropped_spans,
+            export_errors.endpoint.as_deref().unwrap_or("the collector")
+        );
+        for (message, count) in export_errors.errors {
+            summary.push_str(&format!("\n  - {} x {}", count, message));
+        }
+        Some(summary)
+    }
+}
+
+// ErrorReportingExporter が集計済みの送信エラー。error handler で、集計済みのエラーを見分けるためのもの。
+#[derive(Debug)]
+struct CountedExportError(String);
+
+impl fmt::Display for CountedExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CountedExportError {}
+
+// 既定の error handler はエラーのたびに標準エラーへ出力するため、ErrorReportingExporter が集計した送信エラーは出力しない。
+// batch の queue が一杯の場合や、送信のタイムアウト、flush / shutdown の失敗等、それ以外のエラーは既定の handler と同じく出力する。
+pub(crate) fn install_error_handler() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let result = global::set_error_handler(|error| match error {
+            Error::Trace(TraceError::Other(e)) if e.is::<CountedExportError>() => {}
+            other => eprintln!("OpenTelemetry error occurred. {}", other),
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to set the OpenTelemetry error handler: {}", e);
+        }
+    });
+}
+
+// 送信に失敗した span の数を数える exporter。exporter を作れなかった場合も、送信のたびにエラーとして数える。
+#[derive(Debug)]
+pub(crate) struct ErrorReportingExporter {
+    endpoint: String,
+    inner: Result<Box<dyn SpanExporter>, String>,
+    counter: ExportErrorCounter,
+}
+
+impl ErrorReportingExporter {
+    pub(crate) fn new(endpoint: &str, inner: Result<Box<dyn SpanExporter>, TraceError>) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            inner: inner.map_err(|e| e.to_string()),
+            counter: ExportErrorCounter::default(),
+        }
+    }
+
+    pub(crate) fn counter(&self) -> ExportErrorCounter {
+        self.counter.clone()
+    }
+}
+
+impl SpanExporter for ErrorReportingExporter {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        let span_count = batch.len();
+        let endpoint = self.endpoint.clone();
+        let counter = self.counter.clone();
+        let result = match &mut self.inner {
+            Ok(inner) => inner.export(batch),
+            Err(e) => future::ready(Err(TraceError::Other(
+                format!("the exporter is not available: {}", e).into(),
+            )))
+            .boxed(),
+        };
+        result
+            .map(move |result| {
+                result.map_err(|e| {
+                    let message = e.to_string();
+                    counter.record(&endpoint, span_count, message.clone());
+                    TraceError::Other(Box::new(CountedExportError(message)))
+                })
+            })
+            .boxed()
+    }
+
+    fn shutdown(&mut self) {
+        if let Ok(inner) = &mut self.inner {
+            inner.shutdown();
+        }
+    }
+
+    fn force_flush(&mut self) -> BoxFuture<'static, ExportResult> {
+        match &mut self.inner {
+            Ok(inner) => inner.force_flush(),
+            Err(_) => future::ready(Ok(())).boxed(),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crate::export_error::ErrorReportingExporter;
+use deps::futures::future::{self, BoxFuture};
+use deps::futures::FutureExt;
+use deps::opentelemetry::trace::{Status, TraceError};
+use deps::opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+use deps::serde_json::{json, Map, Value};
+use options::{parse_fallback_name, FallbackName};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+
+pub(crate) const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+// collector に接続できなかった場合の span の送り先。OTEL_TEST_FALLBACK_SINK 環境変数の値と同じ形式で指定する。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum FallbackSink {
+    // span を捨てる。
+    #[default]
+    NoOp,
+    // span を 1 行 1 span の JSON として標準出力に書く。
+    Stdout,
+    // span を 1 行 1 span の JSON としてファイルに追記する。"file:<path>" で指定する。
+    File(PathBuf),
+}
+
+impl FromStr for FallbackSink {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match parse_fallback_name(s)? {
+            FallbackName::NoOp => FallbackSink::NoOp,
+            FallbackName::Stdout => FallbackSink::Stdout,
+            FallbackName::File(path) => FallbackSink::File(PathBuf::from(path)),
+        })
+    }
+}
+
+impl std::fmt::Display for FallbackSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FallbackSink::NoOp => write!(f, "noop (spans are discarded)"),
+            FallbackSink::Stdout => write!(f, "stdout"),
+            FallbackSink::File(path) => write!(f, "file {}", path.display()),
+        }
+    }
+}
+
+impl FallbackSink {
+    // OTEL_TEST_FALLBACK_SINK が指定されている場合は、コード上の指定より優先する。
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("OTEL_TEST_FALLBACK_SINK").ok()?;
+        value
+            .parse()
+            .map_err(|e| println!("OTEL_TEST_FALLBACK_SINK is ignored: {}", e))
+            .ok()
+    }
+
+    fn exporter(&self) -> io::Result<Box<dyn SpanExporter>> {
+        Ok(match self {
+            FallbackSink::NoOp => Box::new(NoOpExporter),
+            FallbackSink::Stdout => Box::new(JsonLinesExporter::new(io::stdout())),
+            FallbackSink::File(path) => Box::new(JsonLinesExporter::new(
+                OpenOptions::new().create(true).append(true).open(path)?,
+            )),
+        })
+    }
+}
+
+// OTEL_TEST_PROBE_TIMEOUT_MS が指定されている場合は、コード上の指定より優先する。0 の場合は接続を確認しない。
+pub(crate) fn probe_timeout_from_env() -> Option<Option<Duration>> {
+    let value = std::env::var("OTEL_TEST_PROBE_TIMEOUT_MS").ok()?;
+    value
+        .trim()
+        .parse()
+        .map(|ms| (ms > 0).then(|| Duration::from_millis(ms)))
+        .map_err(|e| println!("OTEL_TEST_PROBE_TIMEOUT_MS is ignored: {}", e))
+        .ok()
+}
+
+// endpoint ごとに、プロセス内で最初の 1 回だけ接続を確認し、結果を使い回す。
+// 接続できない場合は、どの sink を使うかを最初の 1 回だけ出力する。
+pub(crate) fn select_exporter(
+    collector_endpoint: &str,
+    probe_timeout: Option<Duration>,
+    fallback_sink: &FallbackSink,
+    otlp_exporter: impl FnOnce() -> ErrorReportingExporter,
+) -> ErrorReportingExporter {
+    static REACHABLE: Mutex<Option<HashMap<String, bool>>> = Mutex::new(None);
+
+    let Some(timeout) = probe_timeout else {
+        return otlp_exporter();
+    };
+    let reachable = *REACHABLE
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .entry(collector_endpoint.to_string())
+        .or_insert_with(|| {
+            let reachable = is_reachable(collector_endpoint, timeout);
+            if !reachable {
+                eprintln!(
+                    "otel-util: the collector at {} is not reachable (timeout {:?}); spans are sent to {} for the rest of this run",
+                    collector_endpoint, timeout, fallback_sink
+                );
+            }
+            reachable
+        });
+    if reachable {
+        return otlp_exporter();
+    }
+    // sink を開けなかった場合は、送信エラーとして集計される。
+    ErrorReportingExporter::new(
+        &fallback_sink.to_string(),
+        fallback_sink
+            .exporter()
+            .map_err(|e| TraceError::Other(Box::new(e))),
+    )
+}
+
+// grpc://localhost:4317 のような endpoint の host:port に、timeout 付きで TCP 接続できるかを確認する。
+fn is_reachable(collector_endpoint: &str, timeout: Duration) -> bool {
+    let authority = collector_endpoint
+        .split_once("://")
+        .map_or(collector_endpoint, |(_, rest)| rest)
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    let address = if authority
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok())
+    {
+        authority.to_string()
+    } else {
+        format!("{}:4317", authority)
+    };
+    let Ok(addresses) = address.to_socket_addrs() else {
+        return false;
+    };
+    addresses
+        .into_iter()
+        .any(|address| TcpStream::connect_timeout(&address, timeout).is_ok())
+}
+
+#[derive(Debug)]
+struct NoOpExporter;
+
+impl SpanExporter for NoOpExporter {
+    fn export(&mut self, _batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        future::ready(Ok(())).boxed()
+    }
+}
+
+// span を 1 行 1 span の JSON として書き出す exporter。
+#[derive(Debug)]
+struct JsonLinesExporter<W> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesExporter<W> {
+    fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    fn write_spans(&mut self, batch: Vec<SpanData>) -> io::Result<()> {
+        for span in batch {
+            writeln!(self.writer, "{}", span_to_json(&span))?;
+        }
+        self.writer.flush()
+    }
+}
+
+impl<W> SpanExporter for JsonLinesExporter<W>
+where
+    W: Write + Send + Sync + std::fmt::Debug,
+{
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        let result = self
+            .write_spans(batch)
+            .map_err(|e| TraceError::Other(Box::new(e)));
+        future::ready(result).boxed()
+    }
+}
+
+fn span_to_json(span: &SpanData) -> Value {
+    let unix_nanos = |time: std::time::SystemTime| {
+        time.duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos() as u64)
+    };
+    let attributes: Map<String, Value> = span
+        .attributes
+        .iter()
+        .map(|kv| (kv.key.to_string(), Value::String(kv.value.to_string())))
+        .collect();
+    json!({
+        "trace_id": span.span_context.trace_id().to_string(),
+        "span_id": span.span_context.span_id().to_string(),
+        "parent_span_id": span.parent_span_id.to_string(),
+        "name": span.name,
+        "start_time_unix_nano": unix_nanos(span.start_time),
+        "end_time_unix_nano": unix_nanos(span.end_time),
+        "status": match &span.status {
+            Status::Unset => "unset".to_string(),
+            Status::Ok => "ok".to_string(),
+            Status::Error { description } => format!("error: {}", description),
+        },
+        "attributes": attributes,
+    })
+}
@@ -0,0 +1,83 @@
+use crate::PropagationContext;
+use deps::http;
+use deps::tonic::metadata::{MetadataKey, MetadataValue};
+use deps::tonic::service::Interceptor;
+use deps::tonic::{Request, Status};
+use deps::tower::{Layer, Service};
+use deps::tracing::{self, instrument::Instrumented, Instrument};
+use deps::tracing_opentelemetry::OpenTelemetrySpanExt;
+use std::task::{Context, Poll};
+
+// tonic の client で、現在の span の context を metadata に設定する interceptor。
+// HelloClient::with_interceptor(channel, TracePropagationInterceptor) のように使う。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracePropagationInterceptor;
+
+impl Interceptor for TracePropagationInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let context = tracing::Span::current().context();
+        for (key, value) in PropagationContext::inject(&context).0 {
+            if let (Ok(key), Ok(value)) = (
+                MetadataKey::from_bytes(key.as_bytes()),
+                MetadataValue::try_from(value),
+            ) {
+                request.metadata_mut().insert(key, value);
+            }
+        }
+        Ok(request)
+    }
+}
+
+// tonic の server で、リクエストの metadata から context を取り出し、その子となる server span でリクエストを処理する layer。
+// Server::builder().layer(TracePropagationLayer).add_service(...) のように使う。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracePropagationLayer;
+
+impl<S> Layer<S> for TracePropagationLayer {
+    type Service = TracePropagationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TracePropagationService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TracePropagationService<S> {
+    inner: S,
+}
+
+// span 名は semantic conventions に従い、"{rpc.service}/{rpc.method}" とする。
+fn server_span<B>(request: &http::Request<B>) -> tracing::Span {
+    let path = request.uri().path().trim_start_matches('/');
+    let (service, method) = path.split_once('/').unwrap_or((path, ""));
+    let span = tracing::info_span!(
+        parent: None,
+        "grpc request",
+        otel.name = path,
+        otel.kind = "server",
+        rpc.system = "grpc",
+        rpc.service = service,
+        rpc.method = method,
+    );
+    span.set_parent(PropagationContext::from(request.headers()).extract());
+    span
+}
+
+impl<S, B> Service<http::Request<B>> for TracePropagationService<S>
+where
+    S: Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Instrumented<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let span = server_span(&request);
+        let future = span.in_scope(|| self.inner.call(request));
+        future.instrument(span)
+    }
+}
@@ -0,0 +1,82 @@
+use crate::PropagationContext;
+use deps::async_trait::async_trait;
+use deps::http::header::{HeaderMap, HeaderName, HeaderValue};
+use deps::reqwest::{Request, RequestBuilder, Response};
+use deps::reqwest_middleware::{Middleware, Next};
+use deps::task_local_extensions::Extensions;
+use deps::tracing;
+use deps::tracing_opentelemetry::OpenTelemetrySpanExt;
+use std::collections::HashMap;
+
+// ヘッダー名は小文字で保持する。同名のヘッダーが複数ある場合 (tracestate, baggage 等) はカンマで連結する。
+impl From<&HeaderMap> for PropagationContext {
+    fn from(headers: &HeaderMap) -> Self {
+        let mut context = HashMap::<String, String>::new();
+        for (name, value) in headers {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+            context
+                .entry(name.as_str().to_string())
+                .and_modify(|joined| {
+                    joined.push(',');
+                    joined.push_str(value);
+                })
+                .or_insert_with(|| value.to_string());
+        }
+        PropagationContext(context)
+    }
+}
+
+impl From<&PropagationContext> for HeaderMap {
+    fn from(context: &PropagationContext) -> Self {
+        let mut headers = HeaderMap::new();
+        context.inject_into_headers(&mut headers);
+        headers
+    }
+}
+
+impl PropagationContext {
+    // 同名のヘッダーは置き換える。ヘッダーとして不正な key, value は無視する。
+    pub fn inject_into_headers(&self, headers: &mut HeaderMap) {
+        for (key, value) in &self.0 {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(key.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+    }
+}
+
+// reqwest の送信時に、現在の span の context をヘッダーに設定する middleware。
+// reqwest_middleware::ClientBuilder::new(client).with(TracePropagationMiddleware).build() のように使う。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracePropagationMiddleware;
+
+#[async_trait]
+impl Middleware for TracePropagationMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> deps::reqwest_middleware::Result<Response> {
+        let context = tracing::Span::current().context();
+        PropagationContext::inject(&context).inject_into_headers(req.headers_mut());
+        next.run(req, extensions).await
+    }
+}
+
+// middleware を使わない reqwest::Client 向けに、リクエスト単位で context を設定する。
+pub trait WithTraceContext {
+    fn with_trace_context(self) -> Self;
+}
+
+impl WithTraceContext for RequestBuilder {
+    fn with_trace_context(self) -> Self {
+        let context = tracing::Span::current().context();
+        self.headers(HeaderMap::from(&PropagationContext::inject(&context)))
+    }
+}
@@ -1,8 +1,59 @@
 use deps::opentelemetry;
-use deps::opentelemetry::{global, propagation::Extractor, propagation::Injector};
+use deps::opentelemetry::trace::TraceContextExt;
+use deps::opentelemetry::{
+    global, propagation::Extractor, propagation::Injector, propagation::TextMapPropagator,
+};
 use deps::serde::{Deserialize, Serialize};
+use deps::tracing_opentelemetry::OpenTelemetrySpanExt;
 use std::collections::HashMap;
 
+pub mod actix;
+mod assertion;
+mod console;
+mod delegating_tracer;
+mod diagnostic;
+mod export;
+mod export_error;
+mod fallback;
+mod grpc;
+mod http;
+mod log_bridge;
+mod log_filter;
+mod otlp;
+mod propagator;
+#[cfg(feature = "rayon")]
+pub mod rayon;
+mod resource;
+mod spawn;
+mod step;
+mod test_settings;
+pub mod thread;
+mod trace_link;
+mod traced;
+pub use assertion::{assertion_failed, AssertionFailure, SoftAssertions};
+pub use console::ConsoleOutput;
+pub use export::ExportMode;
+use export::{slow_threshold_from_env, OnFailureSpanProcessor};
+pub use export_error::ExportErrorPolicy;
+use export_error::{ErrorReportingExporter, ExportErrorCounter};
+pub use fallback::FallbackSink;
+use fallback::{probe_timeout_from_env, select_exporter, DEFAULT_PROBE_TIMEOUT};
+pub use grpc::*;
+pub use http::*;
+use log_bridge::install_log_bridge;
+pub use log_bridge::{LogBridge, LogBridgeFilter};
+use log_filter::{TestLevelFilter, DEFAULT_LOG_LEVEL};
+pub use otlp::OtlpCompression;
+use otlp::OtlpExporterOptions;
+pub use propagator::*;
+pub use resource::*;
+pub use spawn::*;
+pub use step::{record_step_error, Step, StepFuture, StepGuard};
+use test_settings::{activate_guard, deactivate_guard, TestSettings};
+pub use trace_link::{current_trace_id, span_trace_id, PrintTrace};
+use trace_link::{trace_url, trace_url_from_env, DEFAULT_TRACE_URL};
+pub use traced::*;
+
 // Serializable datastructure to hold the opentelemetry propagation context.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PropagationContext(pub HashMap<String, String>);
@@ -13,15 +64,25 @@ impl PropagationContext {
     }
 
     pub fn inject(context: &opentelemetry::Context) -> Self {
-        global::get_text_map_propagator(|propagator| {
-            let mut propagation_context = PropagationContext::empty();
-            propagator.inject_context(context, &mut propagation_context);
-            propagation_context
-        })
+        global::get_text_map_propagator(|propagator| Self::inject_with(propagator, context))
     }
 
     pub fn extract(&self) -> opentelemetry::Context {
-        global::get_text_map_propagator(|propagator| propagator.extract(self))
+        global::get_text_map_propagator(|propagator| self.extract_with(propagator))
+    }
+
+    // global の propagator ではなく、指定した propagator で inject する。
+    pub fn inject_with(
+        propagator: &dyn TextMapPropagator,
+        context: &opentelemetry::Context,
+    ) -> Self {
+        let mut propagation_context = PropagationContext::empty();
+        propagator.inject_context(context, &mut propagation_context);
+        propagation_context
+    }
+
+    pub fn extract_with(&self, propagator: &dyn TextMapPropagator) -> opentelemetry::Context {
+        propagator.extract(self)
     }
 }
 
@@ -32,9 +93,17 @@ impl Injector for PropagationContext {
 }
 
 impl Extractor for PropagationContext {
+    // HTTP ヘッダー等から作られた場合に備え、key の大文字小文字は区別しない。
     fn get(&self, key: &str) -> Option<&str> {
-        let key = key.to_owned();
-        self.0.get(&key).map(|v| v.as_ref())
+        self.0
+            .get(key)
+            .or_else(|| {
+                self.0
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                    .map(|(_, v)| v)
+            })
+            .map(|v| v.as_ref())
     }
 
     fn keys(&self) -> Vec<&str> {
@@ -42,28 +111,115 @@ impl Extractor for PropagationContext {
     }
 }
 
+// 子プロセスへは、propagator の key を大文字にした環境変数 (TRACEPARENT, TRACESTATE, BAGGAGE 等) で context を渡す。
+fn env_var_name(key: &str) -> String {
+    key.to_ascii_uppercase().replace('-', "_")
+}
+
+impl PropagationContext {
+    pub fn to_env_vars(&self) -> Vec<(String, String)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (env_var_name(key), value.clone()))
+            .collect()
+    }
+
+    pub fn from_env() -> Self {
+        let keys: Vec<String> = global::get_text_map_propagator(|propagator| {
+            propagator.fields().map(|key| key.to_string()).collect()
+        });
+        Self(
+            keys.into_iter()
+                .filter_map(|key| std::env::var(env_var_name(&key)).ok().map(|v| (key, v)))
+                .collect(),
+        )
+    }
+}
+
+// 現在の span の context を、起動する子プロセスの環境変数に設定する。
+// 子プロセス側で guard factory を build すると、子プロセスの root span はこの span の子になる。
+pub trait PropagateTraceContext {
+    fn propagate_trace_context(&mut self) -> &mut Self;
+}
+
+impl PropagateTraceContext for std::process::Command {
+    fn propagate_trace_context(&mut self) -> &mut Self {
+        let context = tracing::Span::current().context();
+        self.envs(PropagationContext::inject(&context).to_env_vars())
+    }
+}
+
+impl PropagateTraceContext for deps::tokio::process::Command {
+    fn propagate_trace_context(&mut self) -> &mut Self {
+        let context = tracing::Span::current().context();
+        self.envs(PropagationContext::inject(&context).to_env_vars())
+    }
+}
+
+use delegating_tracer::DelegatingTracer;
+use deps::opentelemetry::trace::TracerProvider as _;
 use deps::opentelemetry::KeyValue;
-use deps::opentelemetry_otlp;
-use deps::opentelemetry_otlp::WithExportConfig;
+use deps::opentelemetry_otlp::SpanExporterBuilder;
 use deps::opentelemetry_sdk;
+use deps::opentelemetry_sdk::export::trace::SpanExporter;
+use deps::opentelemetry_sdk::resource::ResourceDetector;
 use deps::opentelemetry_sdk::{
     runtime,
-    trace::{BatchConfig, RandomIdGenerator, Sampler, Tracer},
+    trace::{BatchConfig, BatchSpanProcessor, RandomIdGenerator, Sampler, Tracer, TracerProvider},
     Resource,
 };
 use deps::opentelemetry_semantic_conventions::{
     resource::{DEPLOYMENT_ENVIRONMENT, SERVICE_NAME, SERVICE_VERSION},
     SCHEMA_URL,
 };
-use deps::tokio::runtime::Handle;
+use deps::tokio::runtime::{Builder, Runtime};
 use deps::tracing;
-use deps::tracing_core::Level;
-use deps::tracing_opentelemetry::OpenTelemetryLayer;
+use deps::tracing_core::span::{Attributes, Id};
+use deps::tracing_core::Subscriber;
+use deps::tracing_opentelemetry::{OpenTelemetryLayer, OtelData};
 use deps::tracing_subscriber;
-use deps::tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use deps::tracing_subscriber::registry::LookupSpan;
+use deps::tracing_subscriber::{layer, layer::SubscriberExt, util::SubscriberInitExt, Layer};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, OnceLock};
+use std::time::Duration;
+
+// trace の送信は、テスト側の runtime (flavor, start_paused) の設定に影響されないよう、専用の runtime で行う。
+// exporter の初期化をこの runtime 上で行うことで、tonic の接続処理や batch の送信タスクもこの runtime に載る。
+fn export_runtime() -> &'static Runtime {
+    static EXPORT_RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    EXPORT_RUNTIME.get_or_init(|| {
+        Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("otel-util-exporter")
+            .enable_all()
+            .build()
+            .expect("Failed to build the runtime for exporting traces")
+    })
+}
+
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn set_text_map_propagator(propagators: Option<&[PropagatorKind]>) {
+    let propagators = match propagators {
+        Some(propagators) => propagators.to_vec(),
+        None => PropagatorKind::from_env(),
+    };
+    global::set_text_map_propagator(composite_propagator(&propagators));
+}
 
-fn resource(service_name: &str, version: &str) -> Resource {
-    Resource::from_schema_url(
+// detector で検出した値より、コードで指定した service.name 等を優先する。
+fn resource(
+    service_name: &str,
+    version: &str,
+    resource_detectors: &[Box<dyn ResourceDetector>],
+) -> Resource {
+    let detected = resource_detectors
+        .iter()
+        .fold(Resource::empty(), |resource, detector| {
+            resource.merge(&detector.detect(Duration::from_secs(1)))
+        });
+    detected.merge(&Resource::from_schema_url(
         [
             KeyValue::new(SERVICE_NAME, service_name.to_string()),
             KeyValue::new(SERVICE_VERSION, version.to_string()),
@@ -73,135 +229,545 @@ fn resource(service_name: &str, version: &str) -> Resource {
             ),
         ],
         SCHEMA_URL,
-    )
+    ))
 }
 
-fn init_default_batch_tracer(
+fn trace_config(resource: Resource) -> opentelemetry_sdk::trace::Config {
+    opentelemetry_sdk::trace::Config::default()
+        // Customize sampling strategy
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            1.0,
+        ))))
+        // If export trace to AWS X-Ray, you can use XrayIdGenerator
+        .with_id_generator(RandomIdGenerator::default())
+        .with_resource(resource)
+}
+
+// exporter の初期化に失敗した場合も unwrap せず、送信のたびにエラーとして集計されるようにする。
+fn otlp_exporter(
     collector_endpoint: &str,
-    service_name: &str,
-    version: &str,
-) -> Tracer {
-    global::set_text_map_propagator(opentelemetry_sdk::propagation::TraceContextPropagator::new());
-
-    opentelemetry_otlp::new_pipeline()
-        .tracing()
-        .with_trace_config(
-            opentelemetry_sdk::trace::Config::default()
-                // Customize sampling strategy
-                .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
-                    1.0,
-                ))))
-                // If export trace to AWS X-Ray, you can use XrayIdGenerator
-                .with_id_generator(RandomIdGenerator::default())
-                .with_resource(resource(service_name, version)),
-        )
+    options: &OtlpExporterOptions,
+) -> ErrorReportingExporter {
+    let _export_runtime = export_runtime().enter();
+    let exporter = options
+        .tonic_builder(collector_endpoint)
+        .and_then(|builder| SpanExporterBuilder::from(builder).build_span_exporter())
+        .map(|exporter| Box::new(exporter) as Box<dyn SpanExporter>);
+    ErrorReportingExporter::new(collector_endpoint, exporter)
+}
+
+// tracer は provider を弱参照で持つため、guard が provider を持ち続ける。global の tracer provider には設定しない。
+fn guard_tracer(provider: &TracerProvider) -> Tracer {
+    provider.versioned_tracer(
+        "opentelemetry-otlp",
+        Some(env!("CARGO_PKG_VERSION")),
+        Some(SCHEMA_URL),
+        None,
+    )
+}
+
+fn init_default_batch_tracer_provider(
+    exporter: ErrorReportingExporter,
+    resource: Resource,
+) -> TracerProvider {
+    let _export_runtime = export_runtime().enter();
+    let processor = BatchSpanProcessor::builder(exporter, runtime::Tokio)
         .with_batch_config(BatchConfig::default())
-        .with_exporter(
-            opentelemetry_otlp::new_exporter()
-                .tonic()
-                .with_endpoint(collector_endpoint),
-        )
-        .install_batch(runtime::Tokio)
-        .unwrap()
+        .build();
+    TracerProvider::builder()
+        .with_span_processor(processor)
+        .with_config(trace_config(resource))
+        .build()
 }
 
-fn init_default_simple_tracer(
-    collector_endpoint: &str,
-    service_name: &str,
-    version: &str,
-) -> Tracer {
-    global::set_text_map_propagator(opentelemetry_sdk::propagation::TraceContextPropagator::new());
-
-    opentelemetry_otlp::new_pipeline()
-        .tracing()
-        .with_trace_config(
-            opentelemetry_sdk::trace::Config::default()
-                // Customize sampling strategy
-                .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
-                    1.0,
-                ))))
-                // If export trace to AWS X-Ray, you can use XrayIdGenerator
-                .with_id_generator(RandomIdGenerator::default())
-                .with_resource(resource(service_name, version)),
-        )
-        .with_exporter(
-            opentelemetry_otlp::new_exporter()
-                .tonic()
-                .with_endpoint(collector_endpoint),
-        )
-        .install_simple()
-        .unwrap()
+fn init_default_simple_tracer_provider(
+    exporter: ErrorReportingExporter,
+    resource: Resource,
+) -> TracerProvider {
+    TracerProvider::builder()
+        .with_simple_exporter(exporter)
+        .with_config(trace_config(resource))
+        .build()
 }
 
-pub fn init_otlp_subscribers(tracer: Tracer) -> OtelGuard {
-    let subscriber = tracing_subscriber::registry()
-        .with(tracing_subscriber::filter::LevelFilter::from_level(
-            Level::INFO,
+// 失敗したテストの trace だけを送信する tracer。batch / simple の区別はなく、判定した trace 単位でまとめて送信する。
+fn init_on_failure_tracer_provider(
+    exporter: ErrorReportingExporter,
+    resource: Resource,
+    slow_threshold: Option<Duration>,
+) -> TracerProvider {
+    TracerProvider::builder()
+        .with_span_processor(OnFailureSpanProcessor::new(
+            Box::new(exporter),
+            slow_threshold,
         ))
-        .with(tracing_subscriber::fmt::layer())
-        .with(OpenTelemetryLayer::new(tracer.clone()));
+        .with_config(trace_config(resource))
+        .build()
+}
 
-    if subscriber.try_init().is_err() {
-        println!("Tracer is already set.");
+// 親プロセスから環境変数で渡された context を、このプロセスの root span の親にする。
+// `parent: None` で明示的に root とした span (新しい trace + link 等) は対象外とする。
+struct RemoteParentLayer {
+    parent_cx: opentelemetry::Context,
+}
+
+impl RemoteParentLayer {
+    fn from_env() -> Option<Self> {
+        let parent_cx = PropagationContext::from_env().extract();
+        let span_context = parent_cx.span().span_context().clone();
+        span_context
+            .is_valid()
+            .then_some(RemoteParentLayer { parent_cx })
+    }
+}
+
+impl<S> Layer<S> for RemoteParentLayer
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: layer::Context<'_, S>) {
+        if !attrs.is_contextual() {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if span.parent().is_some() {
+            return;
+        }
+        let mut extensions = span.extensions_mut();
+        if let Some(otel_data) = extensions.get_mut::<OtelData>() {
+            if !otel_data.parent_cx.has_active_span() {
+                otel_data.parent_cx = self.parent_cx.clone();
+                otel_data.builder.trace_id = Some(self.parent_cx.span().span_context().trace_id());
+            }
+        }
+    }
+}
+
+// subscriber は 1 プロセスで 1 回だけ登録されるため、2 回目以降の初期化では filter の既定の level だけを差し替える。
+// テストの root span の配下には、OtelGuard::root_span でそのテストの guard の level が適用される (TestSettings)。
+struct LevelFilters {
+    otel: TestLevelFilter,
+    console: TestLevelFilter,
+}
+
+static LEVEL_FILTERS: OnceLock<LevelFilters> = OnceLock::new();
+
+pub fn init_otlp_subscribers(tracer: Tracer) -> OtelGuard {
+    let settings = TestSettings {
+        log_level: Arc::from(DEFAULT_LOG_LEVEL),
+        console_output: ConsoleOutput::from_env().unwrap_or_default(),
+        tracer,
+    };
+    let mut guard = init_otlp_subscribers_with_remote_parent(None, settings);
+    guard.export_error_policy = ExportErrorPolicy::from_env().unwrap_or_default();
+    guard
+}
+
+fn init_otlp_subscribers_with_remote_parent(
+    remote_parent: Option<RemoteParentLayer>,
+    settings: TestSettings,
+) -> OtelGuard {
+    // テストの root span の外には、最後に初期化した guard の設定を適用する。
+    console::set_console_output(settings.console_output);
+    let log_level = &settings.log_level;
+    if let Some(filters) = LEVEL_FILTERS.get() {
+        filters.otel.set_default(log_level);
+        filters.console.set_default(log_level);
+    } else {
+        let otel_filter = TestLevelFilter::otel(log_level);
+        let console_filter = TestLevelFilter::console(log_level);
+        // remote parent と orphan span の検出は OTel layer が作る OtelData を使うため、同じ filter を通す。step の失敗の検出も、送信する span と同じ範囲で行う。
+        // span は、その span が属するテストの guard の tracer で送信する (DelegatingTracer)。
+        let subscriber = tracing_subscriber::registry()
+            .with(console::console_layer().with_filter(console_filter.clone()))
+            .with(
+                delegating_tracer::TracerSelectLayer
+                    .and_then(OpenTelemetryLayer::new(DelegatingTracer::default()))
+                    .and_then(remote_parent)
+                    .and_then(diagnostic::OrphanSpanLayer::default())
+                    .and_then(step::StepErrorLayer)
+                    .with_filter(otel_filter.clone()),
+            );
+
+        if subscriber.try_init().is_err() {
+            println!("Tracer is already set.");
+        } else {
+            let _ = LEVEL_FILTERS.set(LevelFilters {
+                otel: otel_filter,
+                console: console_filter,
+            });
+        }
     }
 
     std::panic::set_hook(Box::new(|panic_info| {
         tracing::error!("panic occurred: {}", panic_info);
     }));
 
-    OtelGuard {}
+    export_error::install_error_handler();
+    delegating_tracer::install_global_tracer_provider();
+
+    let tracer_provider = settings.tracer.provider();
+    let test_settings = Arc::new(settings);
+    activate_guard(&test_settings);
+    OtelGuard {
+        tracer_provider,
+        export_error_policy: ExportErrorPolicy::default(),
+        export_errors: ExportErrorCounter::default(),
+        shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+        print_trace: PrintTrace::from_env().unwrap_or_default(),
+        trace_url: trace_url_from_env().unwrap_or(DEFAULT_TRACE_URL.to_string()),
+        test_settings,
+    }
 }
 
-pub struct DefaultBatchOtelGuardFactory {
+// guard factory。BATCH は、ExportMode::Always の場合に span を batch でまとめて送信するか (false の場合は 1 つずつ送信する)。
+// 設定は DefaultBatchOtelGuardFactory と DefaultSimpleOtelGuardFactory で共通。
+pub struct OtelGuardFactory<const BATCH: bool> {
     collector_endpoint: String,
     service_name: String,
     version: String,
+    propagators: Option<Vec<PropagatorKind>>,
+    export_mode: ExportMode,
+    slow_threshold: Option<Duration>,
+    resource_detectors: Vec<Box<dyn ResourceDetector>>,
+    export_error_policy: ExportErrorPolicy,
+    probe_timeout: Option<Duration>,
+    fallback_sink: FallbackSink,
+    shutdown_timeout: Duration,
+    otlp_options: OtlpExporterOptions,
+    print_trace: PrintTrace,
+    trace_url: String,
+    log_bridge: Option<LogBridgeFilter>,
+    log_level: String,
+    console_output: ConsoleOutput,
 }
 
-impl DefaultBatchOtelGuardFactory {
+impl<const BATCH: bool> OtelGuardFactory<BATCH> {
     pub fn new(collector_endpoint: &str, service_name: &str, version: &str) -> Self {
         Self {
             collector_endpoint: collector_endpoint.to_string(),
             service_name: service_name.to_string(),
             version: version.to_string(),
+            propagators: None,
+            export_mode: ExportMode::Always,
+            slow_threshold: None,
+            resource_detectors: default_resource_detectors(),
+            export_error_policy: ExportErrorPolicy::Warn,
+            probe_timeout: Some(DEFAULT_PROBE_TIMEOUT),
+            fallback_sink: FallbackSink::NoOp,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            otlp_options: OtlpExporterOptions::default(),
+            print_trace: PrintTrace::OnFailure,
+            trace_url: DEFAULT_TRACE_URL.to_string(),
+            log_bridge: None,
+            log_level: DEFAULT_LOG_LEVEL.to_string(),
+            console_output: ConsoleOutput::Full,
+        }
+    }
+
+    // 未指定の場合は OTEL_PROPAGATORS (既定は tracecontext,baggage) に従う。
+    pub fn with_propagators(mut self, propagators: &[PropagatorKind]) -> Self {
+        self.propagators = Some(propagators.to_vec());
+        self
+    }
+
+    // OTEL_TEST_EXPORT (always / on_failure) が指定されている場合は、そちらを優先する。
+    pub fn with_export_mode(mut self, export_mode: ExportMode) -> Self {
+        self.export_mode = export_mode;
+        self
+    }
+
+    // on_failure の場合に、成功したテストでもこの時間を超えたら送信する。OTEL_TEST_SLOW_THRESHOLD_MS が優先される。
+    pub fn with_slow_threshold(mut self, slow_threshold: Duration) -> Self {
+        self.slow_threshold = Some(slow_threshold);
+        self
+    }
+
+    fn on_failure_slow_threshold(&self) -> Option<Duration> {
+        slow_threshold_from_env().or(self.slow_threshold)
+    }
+
+    // 既定の detector (default_resource_detectors) に追加する。
+    pub fn with_resource_detector(mut self, detector: impl ResourceDetector + 'static) -> Self {
+        self.resource_detectors.push(Box::new(detector));
+        self
+    }
+
+    // 既定の detector を置き換える。空にすると、service.name 等のコードで指定した値だけになる。
+    pub fn with_resource_detectors(mut self, detectors: Vec<Box<dyn ResourceDetector>>) -> Self {
+        self.resource_detectors = detectors;
+        self
+    }
+
+    // OTEL_TEST_EXPORT_ERROR (ignore / warn / fail) が指定されている場合は、そちらを優先する。
+    pub fn with_export_error_policy(mut self, policy: ExportErrorPolicy) -> Self {
+        self.export_error_policy = policy;
+        self
+    }
+
+    // テストの終了時に trace ID と URL を出力するか (既定は失敗時のみ)。OTEL_TEST_PRINT_TRACE が優先される。
+    pub fn with_print_trace(mut self, print_trace: PrintTrace) -> Self {
+        self.print_trace = print_trace;
+        self
+    }
+
+    // 出力する trace viewer の URL。{trace_id} が trace ID に置き換えられる。OTEL_TEST_TRACE_URL が優先される。
+    pub fn with_trace_url(mut self, template: &str) -> Self {
+        self.trace_url = template.to_string();
+        self
+    }
+
+    // 記録する span / event の level (既定は info)。"info,my_crate=debug" のように RUST_LOG と同じ形式でも指定できる。
+    // OTEL_TEST_LOG (trace) と RUST_LOG (trace と標準出力) が優先される。
+    pub fn with_log_level(mut self, level: &str) -> Self {
+        self.log_level = level.to_string();
+        self
+    }
+
+    // 標準出力への出力形式 (既定は tracing_subscriber::fmt の形式)。OTEL_TEST_CONSOLE が優先される。
+    pub fn with_console_output(mut self, output: ConsoleOutput) -> Self {
+        self.console_output = output;
+        self
+    }
+
+    // log crate の record を、現在の span の event として記録する。"warn,hyper=error" のように target ごとの level を指定する。
+    // OTEL_TEST_LOG_BRIDGE が優先される。
+    pub fn with_log_bridge(mut self, directives: &str) -> Self {
+        match directives.parse() {
+            Ok(filter) => self.log_bridge = Some(filter),
+            Err(e) => println!("log bridge `{}` is ignored: {}", directives, e),
         }
+        self
+    }
+
+    // collector への接続確認の timeout (既定は 500ms)。OTEL_TEST_PROBE_TIMEOUT_MS が優先される。
+    pub fn with_probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = Some(timeout);
+        self
+    }
+
+    // 接続を確認せず、常に collector へ送信する。
+    pub fn without_connectivity_probe(mut self) -> Self {
+        self.probe_timeout = None;
+        self
+    }
+
+    // collector に接続できなかった場合の送り先 (既定は NoOp)。OTEL_TEST_FALLBACK_SINK が優先される。
+    pub fn with_fallback_sink(mut self, fallback_sink: FallbackSink) -> Self {
+        self.fallback_sink = fallback_sink;
+        self
+    }
+
+    // guard の drop 時に、残りの span の送信と終了処理を待つ上限 (既定は 5 秒)。
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    // TLS の CA 証明書 (PEM)。OTEL_TEST_OTLP_CA_CERT が優先される。
+    pub fn with_tls_ca_certificate(mut self, path: impl Into<PathBuf>) -> Self {
+        self.otlp_options.ca_certificate = Some(path.into());
+        self
+    }
+
+    // TLS のクライアント証明書 (PEM)。秘密鍵と合わせて指定する。OTEL_TEST_OTLP_CLIENT_CERT が優先される。
+    pub fn with_tls_client_certificate(mut self, path: impl Into<PathBuf>) -> Self {
+        self.otlp_options.client_certificate = Some(path.into());
+        self
+    }
+
+    // TLS のクライアント証明書の秘密鍵 (PEM)。OTEL_TEST_OTLP_CLIENT_KEY が優先される。
+    pub fn with_tls_client_key(mut self, path: impl Into<PathBuf>) -> Self {
+        self.otlp_options.client_key = Some(path.into());
+        self
+    }
+
+    // 送信時の gRPC metadata。値の ${VAR} は環境変数の値に置き換える。OTEL_TEST_OTLP_HEADERS が優先される。
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.otlp_options
+            .headers
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    // OTEL_TEST_OTLP_COMPRESSION が優先される。
+    pub fn with_compression(mut self, compression: OtlpCompression) -> Self {
+        self.otlp_options.compression = compression;
+        self
+    }
+
+    // 1 回の送信の timeout。OTEL_TEST_OTLP_TIMEOUT_MS が優先される。
+    pub fn with_export_timeout(mut self, timeout: Duration) -> Self {
+        self.otlp_options.timeout = Some(timeout);
+        self
+    }
+
+    fn span_exporter(&self) -> ErrorReportingExporter {
+        select_exporter(
+            &self.collector_endpoint,
+            probe_timeout_from_env().unwrap_or(self.probe_timeout),
+            &FallbackSink::from_env().unwrap_or(self.fallback_sink.clone()),
+            || {
+                otlp_exporter(
+                    &self.collector_endpoint,
+                    &self.otlp_options.clone().with_env_overrides(),
+                )
+            },
+        )
+    }
+
+    fn resource(&self) -> Resource {
+        resource(&self.service_name, &self.version, &self.resource_detectors)
     }
 
     pub fn build(&self) -> OtelGuard {
-        let tracer =
-            init_default_batch_tracer(&self.collector_endpoint, &self.service_name, &self.version);
-        init_otlp_subscribers(tracer.clone())
+        set_text_map_propagator(self.propagators.as_deref());
+        let exporter = self.span_exporter();
+        let export_errors = exporter.counter();
+        let provider = match ExportMode::from_env().unwrap_or(self.export_mode) {
+            ExportMode::Always if BATCH => {
+                init_default_batch_tracer_provider(exporter, self.resource())
+            }
+            ExportMode::Always => init_default_simple_tracer_provider(exporter, self.resource()),
+            ExportMode::OnFailure => init_on_failure_tracer_provider(
+                exporter,
+                self.resource(),
+                self.on_failure_slow_threshold(),
+            ),
+        };
+        let mut guard = init_otlp_subscribers_with_remote_parent(
+            RemoteParentLayer::from_env(),
+            TestSettings {
+                log_level: Arc::from(self.log_level.as_str()),
+                console_output: ConsoleOutput::from_env().unwrap_or(self.console_output),
+                tracer: guard_tracer(&provider),
+            },
+        );
+        guard.export_error_policy =
+            ExportErrorPolicy::from_env().unwrap_or(self.export_error_policy);
+        guard.export_errors = export_errors;
+        guard.shutdown_timeout = self.shutdown_timeout;
+        guard.print_trace = PrintTrace::from_env().unwrap_or(self.print_trace);
+        guard.trace_url = trace_url_from_env().unwrap_or(self.trace_url.clone());
+        if let Some(filter) = LogBridgeFilter::from_env().or(self.log_bridge.clone()) {
+            install_log_bridge(filter);
+        }
+        guard
     }
 }
 
-pub struct DefaultSimpleOtelGuardFactory {
-    collector_endpoint: String,
-    service_name: String,
-    version: String,
+pub type DefaultBatchOtelGuardFactory = OtelGuardFactory<true>;
+
+pub type DefaultSimpleOtelGuardFactory = OtelGuardFactory<false>;
+
+pub struct OtelGuard {
+    // この guard の span だけを送信する tracer provider。drop で終了する。
+    tracer_provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+    export_error_policy: ExportErrorPolicy,
+    // この guard の exporter の送信エラー。
+    export_errors: ExportErrorCounter,
+    shutdown_timeout: Duration,
+    print_trace: PrintTrace,
+    trace_url: String,
+    // テストの root span に付ける設定。
+    test_settings: Arc<TestSettings>,
 }
 
-impl DefaultSimpleOtelGuardFactory {
-    pub fn new(collector_endpoint: &str, service_name: &str, version: &str) -> Self {
-        Self {
-            collector_endpoint: collector_endpoint.to_string(),
-            service_name: service_name.to_string(),
-            version: version.to_string(),
+impl OtelGuard {
+    // テストの root span を作る。root span とその配下の span / event には、同じプロセスで並行に実行される
+    // 他のテストの設定に関係なく、この guard の level と標準出力の形式が適用される。
+    pub fn root_span(&self, create: impl FnOnce() -> tracing::Span) -> tracing::Span {
+        if let Some(filters) = LEVEL_FILTERS.get() {
+            filters.otel.prepare(&self.test_settings.log_level);
+            filters.console.prepare(&self.test_settings.log_level);
         }
+        test_settings::create_root_span(&self.test_settings, create)
     }
 
-    pub fn build(&self) -> OtelGuard {
-        let tracer =
-            init_default_simple_tracer(&self.collector_endpoint, &self.service_name, &self.version);
-        init_otlp_subscribers(tracer.clone())
+    // 終了した span がすべて送信されるまで待機する。
+    // 送信は専用の runtime で行われるため、テスト側の runtime を止めても送信は進む。
+    pub fn force_flush(&self) {
+        if let Some(provider) = &self.tracer_provider {
+            flush_tracer_provider(provider);
+        }
+    }
+
+    // この guard の exporter で、前回の呼び出し以降に発生した送信エラーを policy に従って報告する。Fail の場合は、集計を Err として返す。
+    // force_flush の後に呼ぶこと。
+    pub fn check_export_errors(&self) -> Result<(), String> {
+        let Some(summary) = self.export_errors.take_summary() else {
+            return Ok(());
+        };
+        match self.export_error_policy {
+            ExportErrorPolicy::Ignore => Ok(()),
+            ExportErrorPolicy::Warn => {
+                eprintln!("{}", summary);
+                Ok(())
+            }
+            ExportErrorPolicy::Fail => Err(summary),
+        }
+    }
+
+    // テストの root span の trace ID と、trace viewer の URL を標準エラーに出力する。
+    // Jaeger 等で service 名から trace を探さずに済むよう、テストの最後に呼ぶ。
+    pub fn print_trace_link(&self, trace_id: Option<&str>, failed: bool) {
+        let print = match self.print_trace {
+            PrintTrace::Never => false,
+            PrintTrace::OnFailure => failed,
+            PrintTrace::Always => true,
+        };
+        if let Some(trace_id) = trace_id.filter(|_| print) {
+            eprintln!(
+                "otel-util: trace {} {}",
+                trace_id,
+                trace_url(&self.trace_url, trace_id)
+            );
+        }
     }
 }
 
-pub struct OtelGuard {}
+fn flush_tracer_provider(provider: &opentelemetry_sdk::trace::TracerProvider) {
+    for result in provider.force_flush() {
+        if let Err(err) = result {
+            global::handle_error(err);
+        }
+    }
+}
 
 impl Drop for OtelGuard {
+    // #[test] や main、tokio 以外の executor から drop されても、テストの runtime の終了中でも動くよう、
+    // 専用のスレッドで flush と終了処理を行い、shutdown_timeout まで完了を待つ。
+    // 終了するのはこの guard の tracer provider だけで、同じプロセスの他の guard の送信には影響しない。
     fn drop(&mut self) {
-        Handle::current().spawn(async {
-            opentelemetry::global::shutdown_tracer_provider();
-        });
+        deactivate_guard(&self.test_settings);
+        let tracer_provider = self.tracer_provider.take();
+        let (done, wait) = mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("otel-util-shutdown".to_string())
+            .spawn(move || {
+                if let Some(provider) = &tracer_provider {
+                    flush_tracer_provider(provider);
+                }
+                // tracer は provider を弱参照で持つため、ここで最後の参照を落とすと processor の終了処理が走る。
+                drop(tracer_provider);
+                let _ = done.send(());
+            });
+        match spawned {
+            Ok(_) => {
+                if wait.recv_timeout(self.shutdown_timeout).is_err() {
+                    eprintln!(
+                        "otel-util: shutting down the tracer provider did not finish within {:?}; remaining spans may be lost",
+                        self.shutdown_timeout
+                    );
+                }
+            }
+            Err(e) => eprintln!("otel-util: failed to spawn the shutdown thread: {}", e),
+        }
+        // drop 中は panic できないため、Fail の場合も出力のみ行う。
+        if let Err(summary) = self.check_export_errors() {
+            eprintln!("{}", summary);
+        }
     }
 }
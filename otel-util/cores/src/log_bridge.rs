@@ -0,0 +1,120 @@
+use deps::tracing_log::format_trace;
+use deps::tracing_log::log::{self, LevelFilter, Log, Metadata, Record};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+// log crate の record を tracing の event に変換するときの、target ごとの level。
+// "warn,hyper=error,my_crate::db=debug" のように、RUST_LOG と同じ形式で指定する。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogBridgeFilter {
+    default: LevelFilter,
+    // (target, level)。target は "::" 区切りの前方一致で、長いものを優先する。
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl FromStr for LogBridgeFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = LogBridgeFilter {
+            default: LevelFilter::Off,
+            targets: Vec::new(),
+        };
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let parse_level = |level: &str| {
+                LevelFilter::from_str(level.trim()).map_err(|_| {
+                    format!(
+                        "unknown level `{}` in `{}`; expected `off`, `error`, `warn`, `info`, `debug` or `trace`",
+                        level.trim(),
+                        directive
+                    )
+                })
+            };
+            match directive.split_once('=') {
+                Some((target, level)) if !target.trim().is_empty() => filter
+                    .targets
+                    .push((target.trim().to_string(), parse_level(level)?)),
+                Some(_) => return Err(format!("invalid directive `{}`", directive)),
+                None => filter.default = parse_level(directive)?,
+            }
+        }
+        filter
+            .targets
+            .sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        Ok(filter)
+    }
+}
+
+impl LogBridgeFilter {
+    // OTEL_TEST_LOG_BRIDGE が指定されている場合は、コード上の指定より優先する。"off" の場合は変換しない。
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("OTEL_TEST_LOG_BRIDGE").ok()?;
+        value
+            .parse()
+            .map_err(|e| println!("OTEL_TEST_LOG_BRIDGE is ignored: {}", e))
+            .ok()
+    }
+
+    fn level(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(prefix, _)| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+// log crate の record を、現在の span の event として記録する logger。
+// 他の logger と組み合わせる場合 (multi logger 等) は、これを直接登録する。
+pub struct LogBridge {
+    filter: LogBridgeFilter,
+}
+
+impl LogBridge {
+    pub fn new(filter: LogBridgeFilter) -> Self {
+        LogBridge { filter }
+    }
+}
+
+impl Log for LogBridge {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.filter.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if self.enabled(record.metadata()) {
+            let _ = format_trace(record);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static INSTALLED: OnceLock<()> = OnceLock::new();
+
+// LogBridge を log crate の logger として登録する。登録はプロセスで 1 回だけで、最初に指定した filter が使われる。
+// 既に別の logger が登録されている場合は、その logger をそのまま使う。
+pub(crate) fn install_log_bridge(filter: LogBridgeFilter) {
+    if filter.max_level() == LevelFilter::Off {
+        return;
+    }
+    INSTALLED.get_or_init(|| {
+        let max_level = filter.max_level();
+        match log::set_boxed_logger(Box::new(LogBridge::new(filter))) {
+            Ok(()) => log::set_max_level(max_level),
+            Err(_) => eprintln!(
+                "otel-util: another `log` logger is already installed; log records are not recorded in the trace"
+            ),
+        }
+    });
+}
@@ -0,0 +1,209 @@
+use crate::test_settings::current_test_settings;
+use deps::tracing_core::callsite::rebuild_interest_cache;
+use deps::tracing_core::span::{Attributes, Id, Record};
+use deps::tracing_core::{Interest, LevelFilter, Metadata, Subscriber};
+use deps::tracing_subscriber::layer::{Context, Filter};
+use deps::tracing_subscriber::registry::LookupSpan;
+use deps::tracing_subscriber::EnvFilter;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+pub(crate) const DEFAULT_LOG_LEVEL: &str = "info";
+
+// trace として送信する span / event の filter。OTEL_TEST_LOG、RUST_LOG、コード上の指定 (既定は info) の順に優先する。
+const OTEL_FILTER_VARS: &[&str] = &["OTEL_TEST_LOG", "RUST_LOG"];
+
+// 標準出力に出す span / event の filter。RUST_LOG、コード上の指定の順に優先する。
+const CONSOLE_FILTER_VARS: &[&str] = &["RUST_LOG"];
+
+// 実際に適用する directive と、その filter。
+struct DirectiveFilter {
+    directives: String,
+    filter: EnvFilter,
+}
+
+// 値は RUST_LOG と同じ形式 ("info,my_crate=debug,hyper=off" 等)。
+fn filter_from_env(vars: &[&str], level: &str) -> DirectiveFilter {
+    for var in vars {
+        let Ok(value) = std::env::var(var) else {
+            continue;
+        };
+        if value.trim().is_empty() {
+            continue;
+        }
+        match EnvFilter::try_new(&value) {
+            Ok(filter) => {
+                return DirectiveFilter {
+                    directives: value,
+                    filter,
+                }
+            }
+            Err(e) => println!("{} is ignored: {}", var, e),
+        }
+    }
+    match EnvFilter::try_new(level) {
+        Ok(filter) => DirectiveFilter {
+            directives: level.to_string(),
+            filter,
+        },
+        Err(e) => {
+            println!("log level `{}` is ignored: {}", level, e);
+            DirectiveFilter {
+                directives: DEFAULT_LOG_LEVEL.to_string(),
+                filter: EnvFilter::new(DEFAULT_LOG_LEVEL),
+            }
+        }
+    }
+}
+
+// テストの root span の配下の span / event には、そのテストの level (TestSettings) を適用する filter。
+// 同じプロセスで並行に実行されるテストが、それぞれの level で記録されるようにするためのもの。
+// テストの外 (root span の配下でない span / event) には、最後に初期化した guard の level を適用する。
+//
+// テストごとに異なる level が有効な間は、callsite 単位の判定の cache (Interest) と max level による事前の除外が使えず、
+// span / event のたびに、現在の span が属するテストをたどって判定する。
+// すべてのテストの level が既定と同じ場合 (level を指定しない場合や、OTEL_TEST_LOG 等の環境変数で上書きされている場合) は、
+// 既定の filter の Interest と max level をそのまま使い、EnvFilter を直接使う場合と同じく判定を省略できるようにする。
+#[derive(Clone)]
+pub(crate) struct TestLevelFilter(Arc<TestLevelFilters>);
+
+struct TestLevelFilters {
+    vars: &'static [&'static str],
+    default: RwLock<Arc<DirectiveFilter>>,
+    by_level: RwLock<HashMap<Arc<str>, Arc<DirectiveFilter>>>,
+}
+
+impl TestLevelFilter {
+    pub(crate) fn otel(level: &str) -> Self {
+        Self::new(OTEL_FILTER_VARS, level)
+    }
+
+    pub(crate) fn console(level: &str) -> Self {
+        Self::new(CONSOLE_FILTER_VARS, level)
+    }
+
+    fn new(vars: &'static [&'static str], level: &str) -> Self {
+        TestLevelFilter(Arc::new(TestLevelFilters {
+            vars,
+            default: RwLock::new(Arc::new(filter_from_env(vars, level))),
+            by_level: RwLock::new(HashMap::new()),
+        }))
+    }
+
+    // テストの外に適用する level を差し替える。環境変数も読み直す。
+    pub(crate) fn set_default(&self, level: &str) {
+        *self.0.default.write().unwrap() = Arc::new(filter_from_env(self.0.vars, level));
+        rebuild_interest_cache();
+    }
+
+    // テストの level の filter を用意する。enabled の中で作ると callsite の登録が漏れるため、root span を作る前に呼ぶ。
+    pub(crate) fn prepare(&self, level: &Arc<str>) {
+        if self.0.by_level.read().unwrap().contains_key(level) {
+            return;
+        }
+        self.0
+            .by_level
+            .write()
+            .unwrap()
+            .entry(level.clone())
+            .or_insert_with(|| Arc::new(filter_from_env(self.0.vars, level)));
+        rebuild_interest_cache();
+    }
+
+    // prepare していない level の場合は既定の filter。
+    fn filter(&self, level: &Arc<str>) -> Arc<DirectiveFilter> {
+        match self.0.by_level.read().unwrap().get(level) {
+            Some(filter) => filter.clone(),
+            None => self.0.default.read().unwrap().clone(),
+        }
+    }
+
+    // 現在の span が属するテストの filter。テストの外の場合は既定の filter。
+    fn current_filter(&self) -> Arc<DirectiveFilter> {
+        match current_test_settings() {
+            Some(settings) => self.filter(&settings.log_level),
+            None => self.0.default.read().unwrap().clone(),
+        }
+    }
+
+    fn all_filters(&self) -> Vec<Arc<DirectiveFilter>> {
+        let mut filters = vec![self.0.default.read().unwrap().clone()];
+        filters.extend(self.0.by_level.read().unwrap().values().cloned());
+        filters
+    }
+
+    // 既定と異なる directive を適用するテストがあるか。
+    fn has_per_test_level(&self) -> bool {
+        let default = self.0.default.read().unwrap().clone();
+        self.0
+            .by_level
+            .read()
+            .unwrap()
+            .values()
+            .any(|filter| filter.directives != default.directives)
+    }
+}
+
+// テストごとに異なる level が有効な間は、callsite 単位の cache は使わず、毎回 enabled で判定する。
+// prepare と set_default で有効な level が変わると、callsite の cache は作り直される。
+impl<S> Filter<S> for TestLevelFilter
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn enabled(&self, meta: &Metadata<'_>, cx: &Context<'_, S>) -> bool {
+        Filter::<S>::enabled(&self.current_filter().filter, meta, cx)
+    }
+
+    // span の field で level を指定する directive ("[span{user=alice}]=debug" 等) は callsite の登録で有効になるため、すべての filter に渡す。
+    fn callsite_enabled(&self, meta: &'static Metadata<'static>) -> Interest {
+        let default = self.0.default.read().unwrap().clone();
+        let interest = Filter::<S>::callsite_enabled(&default.filter, meta);
+        for filter in self.0.by_level.read().unwrap().values() {
+            Filter::<S>::callsite_enabled(&filter.filter, meta);
+        }
+        if self.has_per_test_level() {
+            Interest::sometimes()
+        } else {
+            interest
+        }
+    }
+
+    // いずれかのテストで記録し得る、最も詳細な level。
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        self.all_filters()
+            .iter()
+            .map(|filter| Filter::<S>::max_level_hint(&filter.filter))
+            .try_fold(LevelFilter::OFF, |max, hint| Some(max.max(hint?)))
+    }
+
+    // 同じく、span の状態もすべての filter に渡す。
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        for filter in self.all_filters() {
+            Filter::<S>::on_new_span(&filter.filter, attrs, id, ctx.clone());
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        for filter in self.all_filters() {
+            Filter::<S>::on_record(&filter.filter, id, values, ctx.clone());
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        for filter in self.all_filters() {
+            Filter::<S>::on_enter(&filter.filter, id, ctx.clone());
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        for filter in self.all_filters() {
+            Filter::<S>::on_exit(&filter.filter, id, ctx.clone());
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        for filter in self.all_filters() {
+            Filter::<S>::on_close(&filter.filter, id.clone(), ctx.clone());
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use deps::opentelemetry::trace::TraceError;
+use deps::opentelemetry_otlp::{self, TonicExporterBuilder, WithExportConfig};
+use deps::tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+use deps::tonic::transport::{Certificate, ClientTlsConfig, Identity};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+// OTLP exporter の送信時の圧縮方式。名前は OTEL_TEST_OTLP_COMPRESSION 環境変数の値と同じ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OtlpCompression {
+    #[default]
+    None,
+    Gzip,
+}
+
+impl FromStr for OtlpCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "none" => Ok(OtlpCompression::None),
+            "gzip" => Ok(OtlpCompression::Gzip),
+            other => Err(format!(
+                "unknown compression `{}`; expected `none` or `gzip`",
+                other
+            )),
+        }
+    }
+}
+
+// guard factory で指定する OTLP exporter の接続設定。
+// 各項目は OTEL_TEST_OTLP_* 環境変数が指定されている場合、コード上の指定より優先する。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OtlpExporterOptions {
+    pub(crate) ca_certificate: Option<PathBuf>,
+    pub(crate) client_certificate: Option<PathBuf>,
+    pub(crate) client_key: Option<PathBuf>,
+    // 値に含まれる ${VAR} は、exporter の作成時に環境変数の値に置き換える。
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) compression: OtlpCompression,
+    pub(crate) timeout: Option<Duration>,
+}
+
+impl OtlpExporterOptions {
+    pub(crate) fn with_env_overrides(mut self) -> Self {
+        let path = |name: &str| std::env::var_os(name).map(PathBuf::from);
+        if let Some(ca_certificate) = path("OTEL_TEST_OTLP_CA_CERT") {
+            self.ca_certificate = Some(ca_certificate);
+        }
+        if let Some(client_certificate) = path("OTEL_TEST_OTLP_CLIENT_CERT") {
+            self.client_certificate = Some(client_certificate);
+        }
+        if let Some(client_key) = path("OTEL_TEST_OTLP_CLIENT_KEY") {
+            self.client_key = Some(client_key);
+        }
+        // 同じ名前の header は環境変数の値で置き換え、それ以外は追加する。
+        if let Ok(headers) = std::env::var("OTEL_TEST_OTLP_HEADERS") {
+            match parse_headers(&headers) {
+                Ok(headers) => {
+                    for (name, value) in headers {
+                        self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(&name));
+                        self.headers.push((name, value));
+                    }
+                }
+                Err(e) => println!("OTEL_TEST_OTLP_HEADERS is ignored: {}", e),
+            }
+        }
+        if let Ok(compression) = std::env::var("OTEL_TEST_OTLP_COMPRESSION") {
+            match compression.parse() {
+                Ok(compression) => self.compression = compression,
+                Err(e) => println!("OTEL_TEST_OTLP_COMPRESSION is ignored: {}", e),
+            }
+        }
+        if let Ok(timeout) = std::env::var("OTEL_TEST_OTLP_TIMEOUT_MS") {
+            match timeout.trim().parse() {
+                Ok(ms) => self.timeout = Some(Duration::from_millis(ms)),
+                Err(e) => println!("OTEL_TEST_OTLP_TIMEOUT_MS is ignored: {}", e),
+            }
+        }
+        self
+    }
+
+    // 証明書の読み込みや header の変換に失敗した場合は、送信エラーとして集計されるよう Err を返す。
+    pub(crate) fn tonic_builder(
+        &self,
+        collector_endpoint: &str,
+    ) -> Result<TonicExporterBuilder, TraceError> {
+        let mut builder = opentelemetry_otlp::new_exporter()
+            .tonic()
+            .with_endpoint(collector_endpoint);
+        if let Some(tls_config) = self.tls_config(collector_endpoint)? {
+            builder = builder.with_tls_config(tls_config);
+        }
+        if !self.headers.is_empty() {
+            builder = builder.with_metadata(self.metadata()?);
+        }
+        if self.compression == OtlpCompression::Gzip {
+            builder = builder.with_compression(opentelemetry_otlp::Compression::Gzip);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.with_timeout(timeout);
+        }
+        Ok(builder)
+    }
+
+    // https の endpoint か、証明書が指定されている場合に TLS で接続する。
+    fn tls_config(&self, collector_endpoint: &str) -> Result<Option<ClientTlsConfig>, TraceError> {
+        let is_https = collector_endpoint.starts_with("https://");
+        if !is_https
+            && self.ca_certificate.is_none()
+            && self.client_certificate.is_none()
+            && self.client_key.is_none()
+        {
+            return Ok(None);
+        }
+        let mut tls_config = ClientTlsConfig::new();
+        if let Some(ca_certificate) = &self.ca_certificate {
+            tls_config =
+                tls_config.ca_certificate(Certificate::from_pem(read_pem(ca_certificate)?));
+        }
+        match (&self.client_certificate, &self.client_key) {
+            (Some(certificate), Some(key)) => {
+                tls_config =
+                    tls_config.identity(Identity::from_pem(read_pem(certificate)?, read_pem(key)?));
+            }
+            (None, None) => {}
+            _ => {
+                return Err(TraceError::from(
+                    "both the client certificate and the client key must be specified",
+                ))
+            }
+        }
+        Ok(Some(tls_config))
+    }
+
+    fn metadata(&self) -> Result<MetadataMap, TraceError> {
+        let mut metadata = MetadataMap::new();
+        for (name, value) in &self.headers {
+            let key = MetadataKey::from_bytes(name.as_bytes())
+                .map_err(|e| TraceError::from(format!("invalid header name `{}`: {}", name, e)))?;
+            let value = expand_env(value)?;
+            let value = MetadataValue::try_from(value.as_str()).map_err(|e| {
+                TraceError::from(format!("invalid value for header `{}`: {}", name, e))
+            })?;
+            metadata.insert(key, value);
+        }
+        Ok(metadata)
+    }
+}
+
+fn read_pem(path: &Path) -> Result<Vec<u8>, TraceError> {
+    std::fs::read(path)
+        .map_err(|e| TraceError::from(format!("failed to read {}: {}", path.display(), e)))
+}
+
+// "name=value,name=value" の形式。値には ${VAR} を含められる。
+fn parse_headers(headers: &str) -> Result<Vec<(String, String)>, String> {
+    headers
+        .split(',')
+        .filter(|header| !header.trim().is_empty())
+        .map(|header| match header.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                Ok((name.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(format!(
+                "invalid header `{}`; expected `name=value`",
+                header
+            )),
+        })
+        .collect()
+}
+
+// token 等をソースコードに書かずに済むよう、${VAR} を環境変数の値に置き換える。
+fn expand_env(value: &str) -> Result<String, TraceError> {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + end];
+        let var = std::env::var(name)
+            .map_err(|_| TraceError::from(format!("environment variable `{}` is not set", name)))?;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&var);
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
@@ -0,0 +1,118 @@
+use deps::opentelemetry::propagation::text_map_propagator::FieldIter;
+use deps::opentelemetry::propagation::{Extractor, Injector, TextMapPropagator};
+use deps::opentelemetry::Context;
+use deps::opentelemetry_jaeger;
+use deps::opentelemetry_sdk::propagation::{
+    BaggagePropagator, TextMapCompositePropagator, TraceContextPropagator,
+};
+use deps::opentelemetry_zipkin::{self, B3Encoding};
+use options::{propagator_index, propagator_indexes};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// 組み合わせて使う propagator の種類。名前は OTEL_PROPAGATORS 環境変数の値と同じ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropagatorKind {
+    // W3C Trace Context (traceparent, tracestate)
+    TraceContext,
+    // W3C Baggage (baggage)
+    Baggage,
+    // B3 single header (b3)
+    B3,
+    // B3 multi header (x-b3-traceid, x-b3-spanid, x-b3-sampled, ...)
+    B3Multi,
+    // Jaeger (uber-trace-id, uberctx-*)
+    Jaeger,
+}
+
+impl FromStr for PropagatorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::ALL[propagator_index(s)?])
+    }
+}
+
+impl PropagatorKind {
+    // options::PROPAGATOR_NAMES と同じ順序。
+    const ALL: [PropagatorKind; 5] = [
+        PropagatorKind::TraceContext,
+        PropagatorKind::Baggage,
+        PropagatorKind::B3,
+        PropagatorKind::B3Multi,
+        PropagatorKind::Jaeger,
+    ];
+
+    pub const DEFAULT: [PropagatorKind; 2] =
+        [PropagatorKind::TraceContext, PropagatorKind::Baggage];
+
+    // "tracecontext,baggage,b3" のようなカンマ区切りの指定を読む。"none" は propagator なしを表す。
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, String> {
+        Ok(propagator_indexes(s)?
+            .into_iter()
+            .map(|index| Self::ALL[index])
+            .collect())
+    }
+
+    // OTEL_PROPAGATORS が未指定、または不正な場合は tracecontext,baggage を使う。
+    pub fn from_env() -> Vec<Self> {
+        match std::env::var("OTEL_PROPAGATORS") {
+            Ok(value) => Self::parse_list(&value).unwrap_or_else(|e| {
+                println!("OTEL_PROPAGATORS is ignored: {}", e);
+                Self::DEFAULT.to_vec()
+            }),
+            Err(_) => Self::DEFAULT.to_vec(),
+        }
+    }
+
+    pub fn build(&self) -> Box<dyn TextMapPropagator + Send + Sync> {
+        match self {
+            PropagatorKind::TraceContext => Box::new(TraceContextPropagator::new()),
+            PropagatorKind::Baggage => Box::new(BaggagePropagator::new()),
+            PropagatorKind::B3 => Box::new(B3SingleHeaderPropagator::new()),
+            PropagatorKind::B3Multi => Box::new(opentelemetry_zipkin::Propagator::with_encoding(
+                B3Encoding::MultipleHeader,
+            )),
+            PropagatorKind::Jaeger => Box::new(opentelemetry_jaeger::Propagator::new()),
+        }
+    }
+}
+
+// opentelemetry-zipkin の SingleHeader は x-b3-* ヘッダーも inject してしまうため、b3 ヘッダーのみに絞る。
+#[derive(Debug)]
+struct B3SingleHeaderPropagator {
+    inner: opentelemetry_zipkin::Propagator,
+    fields: [String; 1],
+}
+
+impl B3SingleHeaderPropagator {
+    fn new() -> Self {
+        B3SingleHeaderPropagator {
+            inner: opentelemetry_zipkin::Propagator::with_encoding(B3Encoding::SingleHeader),
+            fields: ["b3".to_string()],
+        }
+    }
+}
+
+impl TextMapPropagator for B3SingleHeaderPropagator {
+    fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+        let mut headers = HashMap::new();
+        self.inner.inject_context(cx, &mut headers);
+        if let Some(value) = headers.remove("b3") {
+            injector.set("b3", value);
+        }
+    }
+
+    fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+        self.inner.extract_with_context(cx, extractor)
+    }
+
+    fn fields(&self) -> FieldIter<'_> {
+        FieldIter::new(&self.fields)
+    }
+}
+
+// 指定した propagator をすべて使う composite propagator を作る。inject は全形式で行い、extract は後の指定が優先される。
+pub fn composite_propagator(kinds: &[PropagatorKind]) -> TextMapCompositePropagator {
+    TextMapCompositePropagator::new(kinds.iter().map(PropagatorKind::build).collect())
+}
@@ -0,0 +1,41 @@
+use crate::SoftAssertions;
+use deps::rayon;
+use deps::tracing;
+
+// rayon::scope と同じだが、scope 内で spawn した処理が、spawn した時点の span (と otel の context、soft assertion の集計先) を引き継ぐ。
+pub fn scope<'scope, OP, R>(op: OP) -> R
+where
+    OP: FnOnce(&TracedScope<'_, 'scope>) -> R + Send,
+    R: Send,
+{
+    let span = tracing::Span::current();
+    let context = deps::opentelemetry::Context::current();
+    let soft_assertions = SoftAssertions::current();
+    rayon::scope(|scope| {
+        let _context = context.attach();
+        SoftAssertions::in_optional_scope(soft_assertions.as_ref(), || {
+            span.in_scope(|| op(&TracedScope { scope }))
+        })
+    })
+}
+
+pub struct TracedScope<'a, 'scope> {
+    scope: &'a rayon::Scope<'scope>,
+}
+
+impl<'scope> TracedScope<'_, 'scope> {
+    pub fn spawn<BODY>(&self, body: BODY)
+    where
+        BODY: for<'a> FnOnce(&TracedScope<'a, 'scope>) + Send + 'scope,
+    {
+        let span = tracing::Span::current();
+        let context = deps::opentelemetry::Context::current();
+        let soft_assertions = SoftAssertions::current();
+        self.scope.spawn(move |scope| {
+            let _context = context.attach();
+            SoftAssertions::in_optional_scope(soft_assertions.as_ref(), || {
+                span.in_scope(|| body(&TracedScope { scope }))
+            })
+        });
+    }
+}
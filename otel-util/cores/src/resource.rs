@@ -0,0 +1,195 @@
+use deps::opentelemetry::KeyValue;
+use deps::opentelemetry_sdk::resource::{
+    OsResourceDetector, ProcessResourceDetector, ResourceDetector,
+};
+use deps::opentelemetry_sdk::Resource;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// guard factory が既定で使う detector。後に並んだ detector の値が優先される。
+pub fn default_resource_detectors() -> Vec<Box<dyn ResourceDetector>> {
+    vec![
+        Box::new(OsResourceDetector),
+        Box::new(ProcessResourceDetector),
+        Box::new(HostResourceDetector),
+        Box::new(RustToolchainResourceDetector),
+        Box::new(GitResourceDetector),
+        Box::new(CiResourceDetector),
+    ]
+}
+
+// host.name, host.arch
+#[derive(Debug, Default)]
+pub struct HostResourceDetector;
+
+impl ResourceDetector for HostResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let host_name = fs::read_to_string("/proc/sys/kernel/hostname")
+            .or_else(|_| fs::read_to_string("/etc/hostname"))
+            .ok()
+            .or_else(|| std::env::var("HOSTNAME").ok())
+            .or_else(|| std::env::var("COMPUTERNAME").ok())
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        Resource::new(
+            host_name
+                .map(|name| KeyValue::new("host.name", name))
+                .into_iter()
+                .chain([KeyValue::new("host.arch", std::env::consts::ARCH)]),
+        )
+    }
+}
+
+// cores をビルドした rustc のバージョンと profile (debug / release)。値は build.rs で埋め込む。
+#[derive(Debug, Default)]
+pub struct RustToolchainResourceDetector;
+
+impl ResourceDetector for RustToolchainResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let description = env!("OTEL_UTIL_RUSTC_VERSION");
+        // "rustc 1.75.0 (82e1608df 2023-12-21)" のバージョン部分
+        let version = description.split_whitespace().nth(1).unwrap_or_default();
+        Resource::new([
+            KeyValue::new("process.runtime.name", "rustc"),
+            KeyValue::new("process.runtime.version", version),
+            KeyValue::new("process.runtime.description", description),
+            KeyValue::new("rust.build.profile", env!("OTEL_UTIL_BUILD_PROFILE")),
+        ])
+    }
+}
+
+// vcs.commit.id, vcs.branch.name。git コマンドは使わず、.git 配下のファイルを直接読む。
+// cargo test の実行時に設定される CARGO_MANIFEST_DIR (なければカレントディレクトリ) から、親ディレクトリへ .git を探す。
+#[derive(Debug, Default)]
+pub struct GitResourceDetector;
+
+impl ResourceDetector for GitResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let start_dir = std::env::var("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .or_else(|_| std::env::current_dir());
+        let Some(git_dir) = start_dir.ok().and_then(|dir| find_git_dir(&dir)) else {
+            return Resource::empty();
+        };
+        let Ok(head) = fs::read_to_string(git_dir.join("HEAD")) else {
+            return Resource::empty();
+        };
+
+        let mut attributes = Vec::new();
+        match head.trim().strip_prefix("ref: ") {
+            Some(reference) => {
+                if let Some(commit_id) = resolve_ref(&git_dir, reference) {
+                    attributes.push(KeyValue::new("vcs.commit.id", commit_id));
+                }
+                if let Some(branch) = reference.strip_prefix("refs/heads/") {
+                    attributes.push(KeyValue::new("vcs.branch.name", branch.to_string()));
+                }
+            }
+            // detached HEAD
+            None => attributes.push(KeyValue::new("vcs.commit.id", head.trim().to_string())),
+        }
+        Resource::new(attributes)
+    }
+}
+
+// worktree や submodule では .git がファイルになっており、"gitdir: <path>" で実体を指している。
+fn find_git_dir(start_dir: &Path) -> Option<PathBuf> {
+    start_dir.ancestors().find_map(|dir| {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        let content = fs::read_to_string(&dot_git).ok()?;
+        let git_dir = content.trim().strip_prefix("gitdir: ")?;
+        Some(dir.join(git_dir))
+    })
+}
+
+// ref は worktree 固有の git dir、共通の git dir (commondir)、packed-refs の順に探す。
+fn resolve_ref(git_dir: &Path, reference: &str) -> Option<String> {
+    let common_dir = fs::read_to_string(git_dir.join("commondir"))
+        .map(|common_dir| git_dir.join(common_dir.trim()))
+        .unwrap_or_else(|_| git_dir.to_path_buf());
+    [git_dir, common_dir.as_path()]
+        .iter()
+        .find_map(|dir| fs::read_to_string(dir.join(reference)).ok())
+        .map(|commit_id| commit_id.trim().to_string())
+        .or_else(|| {
+            let packed_refs = fs::read_to_string(common_dir.join("packed-refs")).ok()?;
+            packed_refs.lines().find_map(|line| {
+                let (commit_id, name) = line.split_once(' ')?;
+                (name == reference).then(|| commit_id.to_string())
+            })
+        })
+}
+
+// GitHub Actions, GitLab CI, Jenkins の環境変数から、実行中の pipeline の情報を取得する。
+// CI では detached HEAD で checkout されることが多いため、ブランチ名も CI の環境変数で上書きする。
+#[derive(Debug, Default)]
+pub struct CiResourceDetector;
+
+impl ResourceDetector for CiResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let env = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let attributes: Vec<(&str, Option<String>)> = if env("GITHUB_ACTIONS").is_some() {
+            vec![
+                ("cicd.provider.name", Some("github_actions".to_string())),
+                ("cicd.pipeline.name", env("GITHUB_WORKFLOW")),
+                ("cicd.pipeline.run.id", env("GITHUB_RUN_ID")),
+                ("cicd.pipeline.task.name", env("GITHUB_JOB")),
+                (
+                    "cicd.pipeline.run.url.full",
+                    match (
+                        env("GITHUB_SERVER_URL"),
+                        env("GITHUB_REPOSITORY"),
+                        env("GITHUB_RUN_ID"),
+                    ) {
+                        (Some(server), Some(repository), Some(run_id)) => {
+                            Some(format!("{}/{}/actions/runs/{}", server, repository, run_id))
+                        }
+                        _ => None,
+                    },
+                ),
+                ("cicd.worker.name", env("RUNNER_NAME")),
+                ("vcs.commit.id", env("GITHUB_SHA")),
+                (
+                    "vcs.branch.name",
+                    env("GITHUB_HEAD_REF").or_else(|| env("GITHUB_REF_NAME")),
+                ),
+            ]
+        } else if env("GITLAB_CI").is_some() {
+            vec![
+                ("cicd.provider.name", Some("gitlab_ci".to_string())),
+                ("cicd.pipeline.name", env("CI_PROJECT_PATH")),
+                ("cicd.pipeline.run.id", env("CI_PIPELINE_ID")),
+                ("cicd.pipeline.task.name", env("CI_JOB_NAME")),
+                ("cicd.pipeline.run.url.full", env("CI_PIPELINE_URL")),
+                ("cicd.worker.name", env("CI_RUNNER_DESCRIPTION")),
+                ("vcs.commit.id", env("CI_COMMIT_SHA")),
+                ("vcs.branch.name", env("CI_COMMIT_REF_NAME")),
+            ]
+        } else if env("JENKINS_URL").is_some() {
+            vec![
+                ("cicd.provider.name", Some("jenkins".to_string())),
+                ("cicd.pipeline.name", env("JOB_NAME")),
+                ("cicd.pipeline.run.id", env("BUILD_NUMBER")),
+                ("cicd.pipeline.task.name", env("STAGE_NAME")),
+                ("cicd.pipeline.run.url.full", env("BUILD_URL")),
+                ("cicd.worker.name", env("NODE_NAME")),
+                ("vcs.commit.id", env("GIT_COMMIT")),
+                (
+                    "vcs.branch.name",
+                    env("BRANCH_NAME").or_else(|| env("GIT_BRANCH")),
+                ),
+            ]
+        } else {
+            Vec::new()
+        };
+        Resource::new(
+            attributes
+                .into_iter()
+                .filter_map(|(key, value)| value.map(|value| KeyValue::new(key, value))),
+        )
+    }
+}
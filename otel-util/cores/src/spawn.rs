@@ -0,0 +1,35 @@
+use crate::SoftAssertions;
+use deps::opentelemetry::trace::FutureExt;
+use deps::tokio::task::JoinHandle;
+use deps::tracing::{self, Instrument};
+use std::future::Future;
+
+// tokio::spawn と同じだが、現在の span (と otel の context、soft assertion の集計先) を引き継ぐ。
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let future = future.with_current_context().in_current_span();
+    match SoftAssertions::current() {
+        Some(soft_assertions) => {
+            deps::tokio::spawn(async move { soft_assertions.scope(future).await })
+        }
+        None => deps::tokio::spawn(future),
+    }
+}
+
+// tokio::task::spawn_blocking と同じだが、現在の span (と otel の context、soft assertion の集計先) を引き継ぐ。
+pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let span = tracing::Span::current();
+    let context = deps::opentelemetry::Context::current();
+    let soft_assertions = SoftAssertions::current();
+    deps::tokio::task::spawn_blocking(move || {
+        let _context = context.attach();
+        SoftAssertions::in_optional_scope(soft_assertions.as_ref(), || span.in_scope(f))
+    })
+}
@@ -0,0 +1,190 @@
+use deps::tracing::{self, Span};
+use deps::tracing_core::{Event, Level, Subscriber};
+use deps::tracing_opentelemetry::OpenTelemetrySpanExt;
+use deps::tracing_subscriber::registry::LookupSpan;
+use deps::tracing_subscriber::{layer, Layer, Registry};
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// given / when / then 等、テストの段階を表す span。span 名と test.step attribute に段階の名前を記録する。
+// 段階の中で panic した場合、ERROR の event (soft mode の assert の失敗を含む) が記録された場合、err 指定で出力が Err の場合は、
+// span に test.step.failed を、親の span (通常はテストの root span) に test.failed_step を記録する。
+pub struct Step {
+    name: &'static str,
+    span: Span,
+    parent: Span,
+}
+
+impl Step {
+    pub fn new(name: &'static str) -> Self {
+        Step {
+            name,
+            span: tracing::info_span!("step", otel.name = name, test.step = name),
+            parent: Span::current(),
+        }
+    }
+
+    // 同期の block 用。返した guard が drop されるまで、step の span に入る。
+    pub fn enter(&self) -> StepGuard<'_> {
+        StepGuard {
+            step: self,
+            _entered: self.span.enter(),
+        }
+    }
+
+    // async block 用。
+    pub fn instrument<F: Future>(self, future: F) -> StepFuture<F> {
+        StepFuture { step: self, future }
+    }
+
+    fn record_failure(&self) {
+        self.span.set_attribute("test.step.failed", true);
+        self.parent.set_attribute("test.failed_step", self.name);
+    }
+
+    // StepErrorLayer が、この step の span の中で ERROR の event を記録したか。
+    fn error_event_recorded(&self) -> bool {
+        self.span
+            .with_subscriber(|(id, dispatch)| {
+                dispatch
+                    .downcast_ref::<Registry>()
+                    .and_then(|registry| registry.span(id))
+                    .is_some_and(|span| span.extensions().get::<ErrorEventRecorded>().is_some())
+            })
+            .unwrap_or(false)
+    }
+}
+
+impl Drop for Step {
+    fn drop(&mut self) {
+        if self.error_event_recorded() {
+            self.record_failure();
+        }
+    }
+}
+
+pub struct StepGuard<'a> {
+    step: &'a Step,
+    _entered: tracing::span::Entered<'a>,
+}
+
+impl Drop for StepGuard<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.step.record_failure();
+        }
+    }
+}
+
+deps::pin_project_lite::pin_project! {
+    pub struct StepFuture<F> {
+        step: Step,
+        #[pin]
+        future: F,
+    }
+}
+
+impl<F: Future> Future for StepFuture<F> {
+    type Output = F::Output;
+
+    // executor は panic を catch してから future を drop するため、drop 時ではなく poll 中の unwind で失敗を検出する。
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _guard = this.step.enter();
+        this.future.poll(cx)
+    }
+}
+
+// step の span (test.step を持つ span) と、その祖先の step の span の中で ERROR の event が記録されたことを示す extension。
+struct ErrorEventRecorded;
+
+// ERROR の event が記録された step の span に ErrorEventRecorded を付ける。OTel layer と同じ filter を通す。
+pub(crate) struct StepErrorLayer;
+
+impl<S> Layer<S> for StepErrorLayer
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: layer::Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        let Some(scope) = ctx.event_scope(event) else {
+            return;
+        };
+        for span in scope {
+            if span.metadata().fields().field("test.step").is_some() {
+                span.extensions_mut().replace(ErrorEventRecorded);
+            }
+        }
+    }
+}
+
+// step!(name, err, ...) で、step の出力が Err の場合に、step の span に ERROR の event を記録する。
+pub fn record_step_error<T, E: Debug>(output: &Result<T, E>) {
+    if let Err(e) = output {
+        tracing::error!(error = ?e, "step returned an error");
+    }
+}
+
+// step!("given", { ... }) は同期の block を、step!("when", async { ... }).await は async block を、step の span の中で実行する。
+// step!("when", err, async { ... }) のように err を付けると、block の出力 (Result) が Err の場合も step の失敗とする。
+// 出力の型で自動的に切り替えると、型が後から推論される block や panic する block がコンパイルできなくなるため、明示的に指定する。
+#[macro_export]
+macro_rules! step {
+    ($name:expr, err, async move $body:block) => {
+        $crate::Step::new($name).instrument(async move {
+            let __otel_step_output = async move $body.await;
+            $crate::record_step_error(&__otel_step_output);
+            __otel_step_output
+        })
+    };
+    ($name:expr, err, async $body:block) => {
+        $crate::Step::new($name).instrument(async {
+            let __otel_step_output = async $body.await;
+            $crate::record_step_error(&__otel_step_output);
+            __otel_step_output
+        })
+    };
+    ($name:expr, err, $body:block) => {{
+        let __otel_step = $crate::Step::new($name);
+        let __otel_step_guard = __otel_step.enter();
+        let __otel_step_output = $body;
+        $crate::record_step_error(&__otel_step_output);
+        __otel_step_output
+    }};
+    ($name:expr, async move $body:block) => {
+        $crate::Step::new($name).instrument(async move $body)
+    };
+    ($name:expr, async $body:block) => {
+        $crate::Step::new($name).instrument(async $body)
+    };
+    ($name:expr, $body:block) => {{
+        let __otel_step = $crate::Step::new($name);
+        let __otel_step_guard = __otel_step.enter();
+        $body
+    }};
+}
+
+#[macro_export]
+macro_rules! given {
+    ($($body:tt)+) => {
+        $crate::step!("given", $($body)+)
+    };
+}
+
+#[macro_export]
+macro_rules! when {
+    ($($body:tt)+) => {
+        $crate::step!("when", $($body)+)
+    };
+}
+
+#[macro_export]
+macro_rules! then {
+    ($($body:tt)+) => {
+        $crate::step!("then", $($body)+)
+    };
+}
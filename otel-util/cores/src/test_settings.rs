@@ -0,0 +1,89 @@
+use crate::console::ConsoleOutput;
+use deps::opentelemetry_sdk::trace::Tracer;
+use deps::tracing::{dispatcher, span, Dispatch, Span};
+use deps::tracing_subscriber::registry::LookupSpan;
+use deps::tracing_subscriber::Registry;
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+
+// テストごとの設定。テストの root span に付け、root span とその配下の span / event に適用する。
+// subscriber はプロセスで 1 つのため、同じプロセスで並行に実行されるテストの設定が互いに影響しないようにするためのもの。
+#[derive(Debug)]
+pub(crate) struct TestSettings {
+    pub(crate) log_level: Arc<str>,
+    pub(crate) console_output: ConsoleOutput,
+    // このテストの span を送信する tracer。guard の tracer provider を弱参照で持つ。
+    pub(crate) tracer: Tracer,
+}
+
+// テストの root span に付ける extension。
+struct RootSpanSettings(Arc<TestSettings>);
+
+thread_local! {
+    // テストの root span を作る間だけ設定する。root span 自体にもテストの設定を適用するためのもの。
+    static CREATING_ROOT_SPAN: RefCell<Option<Arc<TestSettings>>> = const { RefCell::new(None) };
+}
+
+// テストの root span を作り、テストの設定を付ける。
+pub(crate) fn create_root_span(
+    settings: &Arc<TestSettings>,
+    create: impl FnOnce() -> Span,
+) -> Span {
+    let previous = CREATING_ROOT_SPAN.with(|current| current.replace(Some(settings.clone())));
+    let span = create();
+    CREATING_ROOT_SPAN.with(|current| *current.borrow_mut() = previous);
+    span.with_subscriber(|(id, dispatch)| {
+        if let Some(span) = dispatch
+            .downcast_ref::<Registry>()
+            .and_then(|registry| registry.span(id))
+        {
+            span.extensions_mut()
+                .replace(RootSpanSettings(settings.clone()));
+        }
+    });
+    span
+}
+
+// 指定した span が属するテストの設定。
+// filter ごとの Context では、その filter で除外された root span が見えないため、registry から直接たどる。
+fn root_span_settings(dispatch: &Dispatch, id: &span::Id) -> Option<Arc<TestSettings>> {
+    let registry = dispatch.downcast_ref::<Registry>()?;
+    let root = registry.span(id)?.scope().from_root().next()?;
+    let extensions = root.extensions();
+    extensions
+        .get::<RootSpanSettings>()
+        .map(|settings| settings.0.clone())
+}
+
+// 現在の span が属するテストの設定。テストの外の場合は None。
+pub(crate) fn current_test_settings() -> Option<Arc<TestSettings>> {
+    let from_span = dispatcher::get_default(|dispatch| {
+        root_span_settings(dispatch, dispatch.current_span().id()?)
+    });
+    from_span.or_else(|| CREATING_ROOT_SPAN.with(|current| current.borrow().clone()))
+}
+
+// 指定した span が属するテストの設定。`parent: None` で作った span 等、テストの root span の配下でない場合は、
+// 作った時点の現在の span が属するテストの設定とする。
+pub(crate) fn test_settings_of(id: &span::Id) -> Option<Arc<TestSettings>> {
+    dispatcher::get_default(|dispatch| root_span_settings(dispatch, id))
+        .or_else(current_test_settings)
+}
+
+// drop されていない guard の設定。テストの外の span は、最後に初期化した guard の tracer で送信する。
+static ACTIVE_GUARDS: Mutex<Vec<Arc<TestSettings>>> = Mutex::new(Vec::new());
+
+pub(crate) fn activate_guard(settings: &Arc<TestSettings>) {
+    ACTIVE_GUARDS.lock().unwrap().push(settings.clone());
+}
+
+pub(crate) fn deactivate_guard(settings: &Arc<TestSettings>) {
+    ACTIVE_GUARDS
+        .lock()
+        .unwrap()
+        .retain(|active| !Arc::ptr_eq(active, settings));
+}
+
+pub(crate) fn latest_guard_settings() -> Option<Arc<TestSettings>> {
+    ACTIVE_GUARDS.lock().unwrap().last().cloned()
+}
@@ -0,0 +1,18 @@
+use crate::SoftAssertions;
+use deps::tracing;
+use std::thread::JoinHandle;
+
+// std::thread::spawn と同じだが、現在の span (と otel の context、soft assertion の集計先) を引き継ぐ。
+pub fn spawn<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let span = tracing::Span::current();
+    let context = deps::opentelemetry::Context::current();
+    let soft_assertions = SoftAssertions::current();
+    std::thread::spawn(move || {
+        let _context = context.attach();
+        SoftAssertions::in_optional_scope(soft_assertions.as_ref(), || span.in_scope(f))
+    })
+}
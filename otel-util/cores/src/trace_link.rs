@@ -0,0 +1,67 @@
+use deps::opentelemetry::trace::{TraceContextExt, TraceId};
+use deps::tracing;
+use deps::tracing_opentelemetry::OpenTelemetrySpanExt;
+use std::str::FromStr;
+
+pub(crate) const DEFAULT_TRACE_URL: &str = "http://localhost:16686/trace/{trace_id}";
+
+// テストの終了時に、trace ID と trace viewer の URL を出力するか。名前は OTEL_TEST_PRINT_TRACE 環境変数の値と同じ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrintTrace {
+    Never,
+    // テストが失敗した場合だけ出力する。
+    #[default]
+    OnFailure,
+    Always,
+}
+
+impl FromStr for PrintTrace {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "never" => Ok(PrintTrace::Never),
+            "on_failure" => Ok(PrintTrace::OnFailure),
+            "always" => Ok(PrintTrace::Always),
+            other => Err(format!(
+                "unknown print trace mode `{}`; expected `never`, `on_failure` or `always`",
+                other
+            )),
+        }
+    }
+}
+
+impl PrintTrace {
+    // OTEL_TEST_PRINT_TRACE が指定されている場合は、コード上の指定より優先する。
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("OTEL_TEST_PRINT_TRACE").ok()?;
+        value
+            .parse()
+            .map_err(|e| println!("OTEL_TEST_PRINT_TRACE is ignored: {}", e))
+            .ok()
+    }
+}
+
+// OTEL_TEST_TRACE_URL が指定されている場合は、コード上の指定より優先する。
+pub(crate) fn trace_url_from_env() -> Option<String> {
+    std::env::var("OTEL_TEST_TRACE_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+}
+
+// 現在の span が属する trace の ID (32 桁の 16 進数)。計装されていない場合は None。
+// テスト本体や assert のメッセージで、trace を探す手がかりとして使う。
+pub fn current_trace_id() -> Option<String> {
+    span_trace_id(&tracing::Span::current())
+}
+
+// 指定した span が属する trace の ID。span の終了後も使えるよう、文字列で返す。
+pub fn span_trace_id(span: &tracing::Span) -> Option<String> {
+    let trace_id = span.context().span().span_context().trace_id();
+    (trace_id != TraceId::INVALID).then(|| trace_id.to_string())
+}
+
+// URL のテンプレート中の {trace_id} を trace ID に置き換える。
+pub(crate) fn trace_url(template: &str, trace_id: &str) -> String {
+    template.replace("{trace_id}", trace_id)
+}
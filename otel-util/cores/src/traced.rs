@@ -0,0 +1,44 @@
+use crate::PropagationContext;
+use deps::opentelemetry::trace::TraceContextExt;
+use deps::serde::{Deserialize, Serialize};
+use deps::tracing;
+use deps::tracing_opentelemetry::OpenTelemetrySpanExt;
+
+// メッセージと一緒に、送信側の span の context を運ぶための入れ物。
+// 受信側では、送信側の span の子 (into_inner_with_span) か、送信側の span へ link した
+// 新しい trace の root (into_inner_with_linked_span) として処理の span を作る。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Traced<T> {
+    pub context: PropagationContext,
+    pub payload: T,
+}
+
+impl<T> Traced<T> {
+    // 現在の span の context を取り込む。
+    pub fn new(payload: T) -> Self {
+        Traced {
+            context: PropagationContext::inject(&tracing::Span::current().context()),
+            payload,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.payload
+    }
+
+    // 送信側の span の子となる span を作る。リクエスト/レスポンスのように、送信側の処理の一部として扱う場合に使う。
+    pub fn into_inner_with_span(self, name: &str) -> (T, tracing::Span) {
+        let span = tracing::info_span!(parent: None, "traced message", otel.name = name);
+        span.set_parent(self.context.extract());
+        (self.payload, span)
+    }
+
+    // 送信側の span へ link した、新しい trace の root span を作る。非同期のメッセージングのように、
+    // 送信側の処理とは独立して扱う場合に使う。
+    pub fn into_inner_with_linked_span(self, name: &str) -> (T, tracing::Span) {
+        let span = tracing::info_span!(parent: None, "traced message", otel.name = name);
+        let context = self.context.extract();
+        span.add_link(context.span().span_context().clone());
+        (self.payload, span)
+    }
+}
@@ -1,11 +1,30 @@
+pub use actix_http;
+pub use actix_test;
+pub use actix_web;
+pub use async_trait;
+pub use awc;
 pub use futures;
+pub use http;
 pub use opentelemetry;
+pub use opentelemetry_jaeger;
 pub use opentelemetry_otlp;
 pub use opentelemetry_sdk;
 pub use opentelemetry_semantic_conventions;
+pub use opentelemetry_zipkin;
+pub use pin_project_lite;
+#[cfg(feature = "rayon")]
+pub use rayon;
+pub use reqwest;
+pub use reqwest_middleware;
 pub use serde;
+pub use serde_json;
+pub use task_local_extensions;
 pub use tokio;
+pub use tonic;
+pub use tower;
 pub use tracing;
+pub use tracing_actix_web;
 pub use tracing_core;
+pub use tracing_log;
 pub use tracing_opentelemetry;
 pub use tracing_subscriber;
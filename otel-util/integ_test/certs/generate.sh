@@ -0,0 +1,20 @@
+#!/bin/sh
+# TLS の結合テストで使う CA・サーバー・クライアントの証明書を生成する。
+set -eu
+cd "$(dirname "$0")"
+days=36500
+
+openssl req -x509 -newkey rsa:2048 -nodes -days "$days" \
+    -keyout ca.key -out ca.pem -subj "/CN=otel-util test CA"
+
+openssl req -newkey rsa:2048 -nodes -keyout server.key -out server.csr -subj "/CN=localhost"
+printf "subjectAltName=DNS:localhost,IP:127.0.0.1\n" > server.ext
+openssl x509 -req -in server.csr -CA ca.pem -CAkey ca.key -CAcreateserial \
+    -days "$days" -extfile server.ext -out server.pem
+
+openssl req -newkey rsa:2048 -nodes -keyout client.key -out client.csr -subj "/CN=otel-util test client"
+printf "extendedKeyUsage=clientAuth\n" > client.ext
+openssl x509 -req -in client.csr -CA ca.pem -CAkey ca.key -CAcreateserial \
+    -days "$days" -extfile client.ext -out client.pem
+
+rm -f ca.key ca.srl server.csr server.ext client.csr client.ext
@@ -0,0 +1,14 @@
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn ok_test() {}
+
+    #[test]
+    fn failed_test() {
+        panic!("failed_test failed");
+    }
+
+    #[test]
+    #[ignore]
+    fn ignored_test() {}
+}
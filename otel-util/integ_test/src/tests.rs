@@ -1,4 +1,55 @@
+pub mod actix_test;
+pub mod actix_test_case;
+pub mod assertion_test;
+pub mod assertion_test_case;
+pub mod cargo_otel_test;
+pub mod cargo_otel_test_case;
+pub mod console_test;
+pub mod console_test_case;
+pub mod export_error_test;
+pub mod export_error_test_case;
+pub mod export_on_failure_test;
+pub mod export_on_failure_test_case;
+pub mod fallback_test;
+pub mod fallback_test_case;
+pub mod grpc_propagation_test;
+pub mod grpc_propagation_test_case;
+pub mod guard_drop_test;
+pub mod guard_drop_test_case;
+pub mod http_propagation_test;
+pub mod http_propagation_test_case;
+pub mod in_process_collector;
+pub mod instrument_all_test;
+pub mod instrument_all_test_case;
 pub mod integ_test_executor;
+pub mod level_test;
+pub mod level_test_case;
+pub mod log_bridge_test;
+pub mod log_bridge_test_case;
+pub mod macro_args_test;
+pub mod module_test;
+pub mod module_test_case;
 pub mod original_test_case;
 pub mod otel_message_test;
+pub mod otlp_tls_test;
+pub mod otlp_tls_test_case;
+pub mod parameterized_test;
+pub mod parameterized_test_case;
+pub mod process_propagation_test;
+pub mod process_propagation_test_case;
+pub mod propagation_test;
+pub mod resource_test;
+pub mod resource_test_case;
+pub mod runtime_flavor_test;
+pub mod runtime_flavor_test_case;
+pub mod sequential_guard_test;
+pub mod sequential_guard_test_case;
+pub mod spawn_test;
+pub mod spawn_test_case;
+pub mod step_test;
+pub mod step_test_case;
 pub mod trace_contents;
+pub mod trace_link_test;
+pub mod trace_link_test_case;
+pub mod traced_message_test;
+pub mod traced_message_test_case;
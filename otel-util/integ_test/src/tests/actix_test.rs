@@ -0,0 +1,60 @@
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_actix_init_service_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &["tests::actix_test_case::actix_init_service_otel_test"],
+        14326,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec!["GET /hello", "actix_init_service_otel_test", "hello"]
+    );
+    // リクエストの span はテストの span の子、handler の span はリクエストの span の子になる。
+    assert_eq!(
+        result.get_parent_span_name("GET /hello"),
+        Some("actix_init_service_otel_test".to_string())
+    );
+    assert_eq!(
+        result.get_parent_span_name("hello"),
+        Some("GET /hello".to_string())
+    );
+    let request = result.get_span_attributes("GET /hello");
+    assert_eq!(request.get("http.route"), Some(&"/hello".to_string()));
+}
+
+#[tokio::test]
+async fn check_otlp_output_actix_start_otel_test() {
+    // given
+    let executor =
+        InProcessTestExecutor::new(&["tests::actix_test_case::actix_start_otel_test"], 14327);
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec![
+            "GET /hello",
+            "actix_start_otel_test",
+            "hello",
+            "request_hello"
+        ]
+    );
+    // 別スレッドのサーバーの span も、リクエストを送った span の子になる。
+    assert_eq!(
+        result.get_parent_span_name("GET /hello"),
+        Some("request_hello".to_string())
+    );
+    assert_eq!(
+        result.get_parent_span_name("hello"),
+        Some("GET /hello".to_string())
+    );
+}
@@ -0,0 +1,45 @@
+use otel_util::actix_web::{test, web, App};
+use otel_util::{tracing, use_otel_at_test, WithTraceContext};
+
+#[tracing::instrument]
+async fn hello() -> &'static str {
+    "hello"
+}
+
+// actix-web のサービスは Send でないため、LocalSet 上で実行する。
+#[use_otel_at_test(endpoint = "grpc://localhost:14326", local = true)]
+async fn actix_init_service_otel_test() {
+    // given
+    let app =
+        otel_util::actix::init_service(App::new().route("/hello", web::get().to(hello))).await;
+
+    // when
+    let request = test::TestRequest::get().uri("/hello").to_request();
+    let body = test::call_and_read_body(&app, request).await;
+
+    // then
+    assert_eq!(body, "hello");
+}
+
+#[tracing::instrument(skip(server))]
+async fn request_hello(server: &otel_util::actix_test::TestServer) -> String {
+    let mut response = server
+        .get("/hello")
+        .with_trace_context()
+        .send()
+        .await
+        .unwrap();
+    String::from_utf8(response.body().await.unwrap().to_vec()).unwrap()
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14327", local = true)]
+async fn actix_start_otel_test() {
+    // given
+    let server = otel_util::actix::start(|| App::new().route("/hello", web::get().to(hello)));
+
+    // when
+    let body = request_hello(&server).await;
+
+    // then
+    assert_eq!(body, "hello");
+}
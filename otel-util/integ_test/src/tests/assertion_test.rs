@@ -0,0 +1,63 @@
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_assertion_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &[
+            "tests::assertion_test_case::failed_assert_eq_otel_test",
+            "tests::assertion_test_case::soft_assert_otel_test",
+            "tests::assertion_test_case::soft_assert_in_linked_span_otel_test",
+            "tests::assertion_test_case::passed_assert_otel_test",
+        ],
+        14343,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    let events = result.get_span_event_attributes("failed_assert_eq_otel_test", "assertion");
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event["assertion.kind"], "assert_eq");
+    assert_eq!(event["assertion.expression"], "a + b == c");
+    assert_eq!(event["assertion.expected"], "31");
+    assert_eq!(event["assertion.actual"], "30");
+    assert_eq!(event["assertion.message"], "sample_add(10, 20)");
+    assert_eq!(event["assertion.soft"], "false");
+    assert_eq!(
+        event["assertion.location"],
+        "src/tests/assertion_test_case.rs:13:5"
+    );
+
+    let events = result.get_span_event_attributes("soft_assert_otel_test", "assertion");
+    let expressions: Vec<&str> = events
+        .iter()
+        .map(|event| event["assertion.expression"].as_str())
+        .collect();
+    assert_eq!(expressions, vec!["1 + 1 == 3", "sample_add(1, 1) != 3"]);
+    assert!(events.iter().all(|event| event["assertion.soft"] == "true"));
+    assert_eq!(events[1]["assertion.expected"], "!= 3");
+    // soft mode では、失敗した後も処理が続く。
+    assert!(result
+        .get_span_names()
+        .contains(&"after_soft_assert".to_string()));
+
+    // linked span や parent のない span の中の失敗も、その span に記録され、テストを失敗させる。
+    for span_name in ["linked_consumer", "detached_consumer"] {
+        let events = result.get_span_event_attributes(span_name, "assertion");
+        assert_eq!(events.len(), 1, "{}", span_name);
+        assert_eq!(events[0]["assertion.soft"], "true");
+    }
+
+    assert!(result
+        .get_span_event_attributes("passed_assert_otel_test", "assertion")
+        .is_empty());
+    // Error になるのは assert が失敗したテストの root span と、assert が失敗した linked span 等だけ。
+    assert_eq!(
+        result
+            .status_count(opentelemetry_proto::tonic::trace::v1::status::StatusCode::Error as i32),
+        5
+    );
+}
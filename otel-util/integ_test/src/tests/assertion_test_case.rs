@@ -0,0 +1,42 @@
+use otel_util::{otel_assert, otel_assert_eq, otel_assert_ne, tracing, use_otel_at_test, Traced};
+
+#[tracing::instrument]
+fn sample_add(a: u64, b: u64) -> u64 {
+    a + b + 1
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14343")]
+#[should_panic]
+async fn failed_assert_eq_otel_test() {
+    let (a, b) = (10, 20);
+    let c = sample_add(a, b);
+    otel_assert_eq!(a + b, c, "sample_add({}, {})", a, b);
+}
+
+// soft mode の失敗は記録だけ行い、テストの最後にまとめて失敗させる。
+#[use_otel_at_test(endpoint = "grpc://localhost:14343")]
+#[should_panic(expected = "2 soft assertion(s) failed")]
+async fn soft_assert_otel_test() {
+    otel_assert!(soft; 1 + 1 == 3);
+    otel_assert_ne!(soft; sample_add(1, 1), 3);
+    otel_assert_eq!(soft; 2, 2);
+    tracing::info_span!("after_soft_assert").in_scope(|| {});
+}
+
+// root span と別の trace になる span の中の soft mode の失敗も、テストの失敗として集計する。
+#[use_otel_at_test(endpoint = "grpc://localhost:14343")]
+#[should_panic(expected = "2 soft assertion(s) failed")]
+async fn soft_assert_in_linked_span_otel_test() {
+    let (payload, consumer_span) =
+        Traced::new(1 + 1).into_inner_with_linked_span("linked_consumer");
+    consumer_span.in_scope(|| otel_assert_eq!(soft; payload, 3));
+    tracing::info_span!(parent: None, "detached_consumer")
+        .in_scope(|| otel_assert!(soft; 1 + 1 == 3));
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14343")]
+async fn passed_assert_otel_test() {
+    otel_assert!(true);
+    otel_assert_eq!(sample_add(1, 1), 3);
+    otel_assert_ne!(1, 2);
+}
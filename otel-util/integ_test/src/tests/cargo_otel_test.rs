@@ -0,0 +1,119 @@
+use super::in_process_collector::InProcessCollector;
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_cargo_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &["tests::cargo_otel_test_case::cargo_otel_test_recorder"],
+        14323,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    let suite_name = "unittests src/lib.rs (target/debug/deps/sample-0123456789abcdef)";
+    assert_eq!(
+        result.get_span_names(),
+        vec![
+            "cargo test",
+            "tests::failed_test",
+            "tests::ignored_test",
+            "tests::ok_test",
+            suite_name,
+        ]
+    );
+    assert_eq!(result.get_parent_span_name("cargo test"), None);
+    assert_eq!(
+        result.get_parent_span_name(suite_name),
+        Some("cargo test".to_string())
+    );
+    assert_eq!(
+        result.get_parent_span_name("tests::ok_test"),
+        Some(suite_name.to_string())
+    );
+
+    let failed = result.get_span_attributes("tests::failed_test");
+    assert_eq!(failed.get("test.outcome"), Some(&"failed".to_string()));
+    let ignored = result.get_span_attributes("tests::ignored_test");
+    assert_eq!(ignored.get("test.outcome"), Some(&"ignored".to_string()));
+
+    let event_exceptions = result.get_span_event_exceptions();
+    assert_eq!(
+        event_exceptions.get("tests::failed_test"),
+        Some(&vec!["test failed".to_string()])
+    );
+    // 失敗した test, suite, run 全体の 3 つが Error (2) になる。
+    assert_eq!(result.status_count(2), 3);
+}
+
+// cargo-otel-test の binary で fixtures/libtest_sample を実際にテストし、run > suite > test の span を確認する。
+#[tokio::test]
+async fn check_otlp_output_cargo_otel_test_binary() {
+    // given
+    let collector = InProcessCollector::start(14348).await;
+
+    // when
+    let output = tokio::process::Command::new("cargo")
+        .args([
+            "run",
+            "--quiet",
+            "--manifest-path",
+            "../cargo-otel-test/Cargo.toml",
+            "--",
+            "--endpoint",
+            "grpc://localhost:14348",
+            "--manifest-path",
+            "fixtures/libtest_sample/Cargo.toml",
+        ])
+        .output()
+        .await
+        .expect("Failed to execute cargo otel-test");
+
+    // then
+    // failed_test が失敗するため、cargo test と同じく失敗の終了コードになる。
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("test tests::ok_test ... ok"), "{stdout}");
+    assert!(
+        stdout.contains("test tests::failed_test ... FAILED"),
+        "{stdout}"
+    );
+
+    let result = collector.trace_content();
+    let span_names = result.get_span_names();
+    let suite_name = span_names
+        .iter()
+        .find(|name| name.starts_with("unittests src/lib.rs"))
+        .unwrap_or_else(|| panic!("suite span is not found in {span_names:?}"))
+        .clone();
+    assert_eq!(
+        span_names,
+        vec![
+            "cargo test".to_string(),
+            "tests::failed_test".to_string(),
+            "tests::ignored_test".to_string(),
+            "tests::ok_test".to_string(),
+            suite_name.clone(),
+        ]
+    );
+    assert_eq!(result.get_parent_span_name("cargo test"), None);
+    assert_eq!(
+        result.get_parent_span_name(&suite_name),
+        Some("cargo test".to_string())
+    );
+    for test_name in [
+        "tests::failed_test",
+        "tests::ignored_test",
+        "tests::ok_test",
+    ] {
+        assert_eq!(
+            result.get_parent_span_name(test_name),
+            Some(suite_name.clone())
+        );
+    }
+    let failed = result.get_span_attributes("tests::failed_test");
+    assert_eq!(failed.get("test.outcome"), Some(&"failed".to_string()));
+    assert_eq!(result.status_count(2), 3);
+}
@@ -0,0 +1,30 @@
+use cargo_otel_test::{LibtestEvent, TestRunRecorder};
+use otel_util::DefaultBatchOtelGuardFactory;
+
+// `cargo test -- -Z unstable-options --format json --report-time` の出力。
+const LIBTEST_OUTPUT: &str = r#"
+{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::ok_test" }
+{ "type": "test", "event": "started", "name": "tests::failed_test" }
+{ "type": "test", "name": "tests::ignored_test", "event": "ignored" }
+{ "type": "test", "name": "tests::ok_test", "event": "ok", "exec_time": 0.0012 }
+{ "type": "test", "name": "tests::failed_test", "event": "failed", "exec_time": 0.0031, "stdout": "thread 'tests::failed_test' panicked at src/lib.rs:10:9:\nassertion failed\n" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 0.0045 }
+"#;
+
+#[test]
+fn cargo_otel_test_recorder() {
+    let guard =
+        DefaultBatchOtelGuardFactory::new("grpc://localhost:14323", "cargo_otel_test", "0.1.0")
+            .build();
+    let mut recorder = TestRunRecorder::new("cargo test", "cargo test");
+    recorder
+        .set_next_suite_name("unittests src/lib.rs (target/debug/deps/sample-0123456789abcdef)");
+    for line in LIBTEST_OUTPUT.lines() {
+        if let Some(event) = LibtestEvent::parse(line) {
+            recorder.record(event);
+        }
+    }
+    recorder.finish(false);
+    guard.force_flush();
+}
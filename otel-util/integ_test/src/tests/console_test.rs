@@ -0,0 +1,114 @@
+use super::in_process_collector::InProcessCollector;
+
+async fn run_test_case(name: &str, envs: &[(&str, &str)]) -> String {
+    let output = tokio::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            &format!("tests::console_test_case::{}", name),
+            "--exact",
+            "--nocapture",
+        ])
+        .env_remove("RUST_LOG")
+        .env_remove("OTEL_TEST_CONSOLE")
+        .envs(envs.iter().copied())
+        .output()
+        .await
+        .expect("Failed to execute test");
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[tokio::test]
+async fn check_console_output_otel_test() {
+    // given
+    let collector = InProcessCollector::start(14347).await;
+
+    // when
+    let json = run_test_case("json_console_otel_test", &[]).await;
+    let compact = run_test_case("compact_console_otel_test", &[]).await;
+    let off = run_test_case("off_console_otel_test", &[]).await;
+    let off_with_env =
+        run_test_case("off_console_otel_test", &[("OTEL_TEST_CONSOLE", "json")]).await;
+    let tree = run_test_case("tree_console_otel_test", &[]).await;
+    let tree_err = run_test_case("tree_err_console_otel_test", &[]).await;
+    // 2 つのテストを同じプロセスで並行に実行する。
+    let concurrent = tokio::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "tests::console_test_case::concurrent_",
+            "--ignored",
+            "--test-threads=2",
+            "--nocapture",
+        ])
+        .env_remove("RUST_LOG")
+        .env_remove("OTEL_TEST_CONSOLE")
+        .output()
+        .await
+        .expect("Failed to execute test");
+    let concurrent = String::from_utf8_lossy(&concurrent.stdout).to_string();
+
+    // then
+    let result = collector.trace_content();
+    // json では、1 行 1 event の JSON に、OTel の trace ID と span ID が含まれる。
+    let line: serde_json::Value = json
+        .lines()
+        .find(|line| line.contains("json event"))
+        // --nocapture では、テスト名と同じ行に出力される場合がある。
+        .and_then(|line| serde_json::from_str(&line[line.find('{')?..]).ok())
+        .unwrap_or_else(|| panic!("{}", json));
+    assert_eq!(line["level"], "INFO");
+    assert_eq!(line["message"], "json event");
+    assert_eq!(line["fields"]["user"], "alice");
+    assert_eq!(line["span"], "json_span");
+    assert_eq!(
+        line["trace_id"].as_str(),
+        result.get_trace_id("json_console_otel_test").as_deref()
+    );
+    assert_eq!(line["span_id"].as_str().map(str::len), Some(16));
+
+    assert!(compact.contains("compact event"), "{}", compact);
+    assert!(!compact.contains("{test.name="), "{}", compact);
+
+    // off でも trace には記録される。
+    assert!(!off.contains("hidden event"), "{}", off);
+    assert!(result.get_span_event_names()["off_console_otel_test"]
+        .contains(&"hidden event".to_string()));
+    // OTEL_TEST_CONSOLE が優先される。
+    assert!(
+        off_with_env.contains(r#""message":"hidden event""#),
+        "{}",
+        off_with_env
+    );
+
+    // tree では、テストの終了時に span の木を出力する。
+    let start = tree
+        .find("tree_console_otel_test (")
+        .unwrap_or_else(|| panic!("{}", tree));
+    let lines: Vec<&str> = tree[start..].lines().take(4).collect();
+    assert_eq!(lines.len(), 4, "{}", tree);
+    // 子孫の span の失敗は root span に伝わる。
+    assert!(lines[0].ends_with(") error"), "{}", tree);
+    assert!(lines[1].starts_with("├── given ("), "{}", tree);
+    assert!(lines[1].ends_with(") ok"), "{}", tree);
+    assert!(lines[2].starts_with("│   └── prepare ("), "{}", tree);
+    assert!(lines[3].starts_with("└── when ("), "{}", tree);
+    assert!(lines[3].ends_with(") error"), "{}", tree);
+
+    // Err を返したテストの root span も error になる。
+    let start = tree_err
+        .find("tree_err_console_otel_test (")
+        .unwrap_or_else(|| panic!("{}", tree_err));
+    let lines: Vec<&str> = tree_err[start..].lines().take(2).collect();
+    assert!(lines[0].ends_with(") error"), "{}", tree_err);
+    assert!(lines[1].starts_with("└── tree_err_work ("), "{}", tree_err);
+    assert!(lines[1].ends_with(") ok"), "{}", tree_err);
+
+    // 形式はプロセス全体ではなくテストごとに適用される。
+    assert!(
+        concurrent.contains(r#""message":"concurrent json event""#),
+        "{}",
+        concurrent
+    );
+    assert!(
+        !concurrent.contains("concurrent hidden event"),
+        "{}",
+        concurrent
+    );
+}
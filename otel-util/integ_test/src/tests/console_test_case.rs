@@ -0,0 +1,61 @@
+use otel_util::{given, tracing, use_otel_at_test, when};
+use std::sync::Barrier;
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14347", console = "json")]
+async fn json_console_otel_test() {
+    tracing::info_span!("json_span").in_scope(|| {
+        tracing::info!(user = "alice", "json event");
+    });
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14347", console = "compact")]
+async fn compact_console_otel_test() {
+    tracing::info!("compact event");
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14347", console = "off")]
+async fn off_console_otel_test() {
+    tracing::info!("hidden event");
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14347", console = "tree")]
+#[should_panic]
+async fn tree_console_otel_test() {
+    given!({
+        tracing::info_span!("prepare").in_scope(|| {});
+    });
+    when!(async {
+        panic!("failed in when");
+    })
+    .await;
+}
+
+// Err を返したテストも、root span を error として出力する。
+#[use_otel_at_test(endpoint = "grpc://localhost:14347", console = "tree")]
+#[should_panic]
+async fn tree_err_console_otel_test() -> Result<(), String> {
+    tracing::info_span!("tree_err_work").in_scope(|| {});
+    Err("failed".to_string())
+}
+
+// concurrent_ で始まる 2 つのテストは、同じプロセスで並行に実行する。
+// 両方の guard を作った後で event を記録し、それぞれのテストの形式で出力されることを確認する。
+// 互いを待つため、2 つを同時に実行できない通常の cargo test では実行しない。
+static CONCURRENT_GUARDS_BUILT: Barrier = Barrier::new(2);
+static CONCURRENT_EVENTS_RECORDED: Barrier = Barrier::new(2);
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14347", console = "json")]
+#[ignore = "run by check_console_output_otel_test with --test-threads=2"]
+async fn concurrent_json_console_otel_test() {
+    CONCURRENT_GUARDS_BUILT.wait();
+    tracing::info!("concurrent json event");
+    CONCURRENT_EVENTS_RECORDED.wait();
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14347", console = "off")]
+#[ignore = "run by check_console_output_otel_test with --test-threads=2"]
+async fn concurrent_off_console_otel_test() {
+    CONCURRENT_GUARDS_BUILT.wait();
+    tracing::info!("concurrent hidden event");
+    CONCURRENT_EVENTS_RECORDED.wait();
+}
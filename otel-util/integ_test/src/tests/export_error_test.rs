@@ -0,0 +1,112 @@
+async fn run_export_error_otel_test(policy: &str) -> std::process::Output {
+    run_export_error_test_case("export_error_otel_test", policy).await
+}
+
+async fn run_export_error_test_case(test_name: &str, policy: &str) -> std::process::Output {
+    tokio::process::Command::new("cargo")
+        .args([
+            "test",
+            &format!("tests::export_error_test_case::{}", test_name),
+            "--",
+            "--exact",
+            "--nocapture",
+        ])
+        .env("OTEL_TEST_EXPORT_ERROR", policy)
+        // 接続の確認で fallback されないよう、collector への送信を試みさせる。
+        .env("OTEL_TEST_PROBE_TIMEOUT_MS", "0")
+        .output()
+        .await
+        .expect("Failed to execute cargo test")
+}
+
+#[tokio::test]
+async fn check_export_error_warn_otel_test() {
+    // when
+    let output = run_export_error_otel_test("warn").await;
+
+    // then
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(
+        stderr.contains("otel-util: failed to export 2 span(s) to grpc://localhost:14335"),
+        "{}",
+        stderr
+    );
+}
+
+#[tokio::test]
+async fn check_export_error_fail_otel_test() {
+    // when
+    let output = run_export_error_otel_test("fail").await;
+
+    // then
+    // panic のメッセージは、panic hook から tracing のログとして標準出力に出力される。
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "{}", stdout);
+    assert!(
+        stdout.contains("otel-util: failed to export 2 span(s) to grpc://localhost:14335"),
+        "{}",
+        stdout
+    );
+}
+
+#[tokio::test]
+async fn check_export_error_ignore_otel_test() {
+    // when
+    let output = run_export_error_otel_test("ignore").await;
+
+    // then
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(
+        !stderr.contains("otel-util: failed to export"),
+        "{}",
+        stderr
+    );
+}
+
+#[tokio::test]
+async fn check_non_export_error_otel_test() {
+    // when
+    let output = run_export_error_test_case("non_export_error_otel_test", "ignore").await;
+
+    // then
+    // 送信エラーは集計して policy に従うが、それ以外の trace のエラーは policy に関係なく出力する。
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(
+        stderr
+            .contains("OpenTelemetry error occurred. cannot send span to the batch span processor"),
+        "{}",
+        stderr
+    );
+    assert!(
+        !stderr.contains("OpenTelemetry error occurred. Exporter"),
+        "{}",
+        stderr
+    );
+    assert!(
+        !stderr.contains("otel-util: failed to export"),
+        "{}",
+        stderr
+    );
+}
+
+#[tokio::test]
+async fn check_export_errors_per_guard_otel_test() {
+    // when
+    let output = tokio::process::Command::new("cargo")
+        .args([
+            "test",
+            "tests::export_error_test_case::export_errors_per_guard_test",
+        ])
+        .env_remove("OTEL_TEST_EXPORT_ERROR")
+        .output()
+        .await
+        .expect("Failed to execute cargo test");
+
+    // then
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("1 passed"), "{}", stdout);
+}
@@ -0,0 +1,57 @@
+use otel_util::opentelemetry::global;
+use otel_util::opentelemetry::trace::{Span, TraceError, Tracer};
+use otel_util::{tracing, use_otel_at_test, DefaultSimpleOtelGuardFactory, ExportErrorPolicy};
+
+#[tracing::instrument]
+fn unexported_work() {}
+
+// 14335 では collector を起動しないため、span の送信は失敗する。
+#[use_otel_at_test(endpoint = "grpc://localhost:14335")]
+async fn export_error_otel_test() {
+    unexported_work();
+}
+
+// 送信以外の trace のエラーは、集計せずにそのまま出力する。
+#[use_otel_at_test(endpoint = "grpc://localhost:14335")]
+async fn non_export_error_otel_test() {
+    unexported_work();
+    global::handle_error(TraceError::from(
+        "cannot send span to the batch span processor",
+    ));
+}
+
+// 同じプロセスに複数の guard がある場合も、送信エラーは送信に失敗した exporter の guard だけが報告する。
+#[test]
+fn export_errors_per_guard_test() {
+    let other = DefaultSimpleOtelGuardFactory::new(
+        "grpc://localhost:14349",
+        "export_errors_per_guard_test",
+        "0.1.0",
+    )
+    .without_connectivity_probe()
+    .with_export_error_policy(ExportErrorPolicy::Fail)
+    .build();
+    let failing = DefaultSimpleOtelGuardFactory::new(
+        "grpc://localhost:14335",
+        "export_errors_per_guard_test",
+        "0.1.0",
+    )
+    .without_connectivity_probe()
+    .with_export_error_policy(ExportErrorPolicy::Fail)
+    .build();
+
+    // global の tracer provider は、後に build した failing のもの。
+    global::tracer("export_errors_per_guard_test")
+        .start("unexported_span")
+        .end();
+    failing.force_flush();
+    other.force_flush();
+
+    assert_eq!(other.check_export_errors(), Ok(()));
+    let summary = failing.check_export_errors().unwrap_err();
+    assert!(
+        summary.contains("failed to export 1 span(s) to grpc://localhost:14335"),
+        "{}",
+        summary
+    );
+}
@@ -0,0 +1,61 @@
+use super::in_process_collector::InProcessCollector;
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_on_failure_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &[
+            "tests::export_on_failure_test_case::passed_on_failure_otel_test",
+            "tests::export_on_failure_test_case::failed_on_failure_otel_test",
+            "tests::export_on_failure_test_case::err_on_failure_otel_test",
+            "tests::export_on_failure_test_case::slow_on_failure_otel_test",
+        ],
+        14332,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    // 成功したテストの trace は送信されず、失敗 (panic、Err) したテストと遅かったテストの trace は子の span も含めて送信される。
+    assert_eq!(
+        result.get_span_names(),
+        vec![
+            "buffered_work",
+            "buffered_work",
+            "buffered_work",
+            "err_on_failure_otel_test",
+            "failed_on_failure_otel_test",
+            "slow_on_failure_otel_test"
+        ]
+    );
+    // Err は root span の中で ERROR の event として記録される。
+    assert_eq!(
+        result
+            .get_span_event_names()
+            .get("err_on_failure_otel_test"),
+        Some(&vec!["test returned an error".to_string()])
+    );
+}
+
+#[tokio::test]
+async fn check_otlp_output_env_override_on_failure_otel_test() {
+    // given
+    let collector = InProcessCollector::start(14333).await;
+
+    // when
+    tokio::process::Command::new("cargo")
+        .arg("test")
+        .arg("tests::export_on_failure_test_case::env_override_on_failure_otel_test")
+        .env("OTEL_TEST_EXPORT", "always")
+        .output()
+        .await
+        .expect("Failed to execute cargo test");
+
+    // then
+    assert_eq!(
+        collector.trace_content().get_span_names(),
+        vec!["buffered_work", "env_override_on_failure_otel_test"]
+    );
+}
@@ -0,0 +1,40 @@
+use otel_util::{tracing, use_otel_at_test};
+
+#[tracing::instrument]
+fn buffered_work(step: &str) {}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14332", export = "on_failure")]
+async fn passed_on_failure_otel_test() {
+    buffered_work("passed");
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14332", export = "on_failure")]
+#[should_panic]
+async fn failed_on_failure_otel_test() {
+    buffered_work("failed");
+    assert_eq!(1 + 1, 3);
+}
+
+// Err を返したテストも失敗として送信される。
+#[use_otel_at_test(endpoint = "grpc://localhost:14332", export = "on_failure")]
+#[should_panic]
+async fn err_on_failure_otel_test() -> Result<(), String> {
+    buffered_work("err");
+    Err("err_on_failure_otel_test failed".to_string())
+}
+
+#[use_otel_at_test(
+    endpoint = "grpc://localhost:14332",
+    export = "on_failure",
+    slow_threshold_ms = 100
+)]
+async fn slow_on_failure_otel_test() {
+    buffered_work("slow");
+    std::thread::sleep(std::time::Duration::from_millis(200));
+}
+
+// OTEL_TEST_EXPORT=always で実行された場合は、成功しても送信される。
+#[use_otel_at_test(endpoint = "grpc://localhost:14333", export = "on_failure")]
+async fn env_override_on_failure_otel_test() {
+    buffered_work("env_override");
+}
@@ -0,0 +1,56 @@
+use otel_util::serde_json::Value;
+
+#[tokio::test]
+async fn check_fallback_sink_fallback_otel_test() {
+    // given
+    let sink_path =
+        std::env::temp_dir().join(format!("otel-util-fallback-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&sink_path);
+
+    // when
+    let output = tokio::process::Command::new("cargo")
+        .args([
+            "test",
+            "tests::fallback_test_case::fallback_otel_test",
+            "--",
+            "--nocapture",
+        ])
+        .env(
+            "OTEL_TEST_FALLBACK_SINK",
+            format!("file:{}", sink_path.display()),
+        )
+        .output()
+        .await
+        .expect("Failed to execute cargo test");
+
+    // then
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert_eq!(
+        stderr
+            .matches("otel-util: the collector at grpc://localhost:14336 is not reachable")
+            .count(),
+        1,
+        "{}",
+        stderr
+    );
+    assert!(
+        !stderr.contains("otel-util: failed to export"),
+        "{}",
+        stderr
+    );
+
+    let spans: Vec<Value> = std::fs::read_to_string(&sink_path)
+        .expect("Failed to read the fallback sink")
+        .lines()
+        .map(|line| otel_util::serde_json::from_str(line).unwrap())
+        .collect();
+    let mut span_names: Vec<&str> = spans
+        .iter()
+        .map(|span| span["name"].as_str().unwrap())
+        .collect();
+    span_names.sort();
+    assert_eq!(span_names, vec!["fallback_otel_test", "fallback_work"]);
+    assert_eq!(spans[0]["trace_id"], spans[1]["trace_id"]);
+    let _ = std::fs::remove_file(&sink_path);
+}
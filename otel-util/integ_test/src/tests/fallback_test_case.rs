@@ -0,0 +1,10 @@
+use otel_util::{tracing, use_otel_at_test};
+
+#[tracing::instrument]
+fn fallback_work() {}
+
+// 14336 では collector を起動しないため、span は fallback の sink に書き出される。
+#[use_otel_at_test(endpoint = "grpc://localhost:14336", fallback = "stdout")]
+async fn fallback_otel_test() {
+    fallback_work();
+}
@@ -0,0 +1,32 @@
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_grpc_propagation_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &["tests::grpc_propagation_test_case::grpc_propagation_otel_test"],
+        14328,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    let server_span = "grpc.health.v1.Health/Check";
+    assert_eq!(
+        result.get_span_names(),
+        vec!["check_health", server_span, "grpc_propagation_otel_test"]
+    );
+    // サーバーの span は、リクエストを送った span の子になる。
+    assert_eq!(
+        result.get_parent_span_name(server_span),
+        Some("check_health".to_string())
+    );
+    let attributes = result.get_span_attributes(server_span);
+    assert_eq!(attributes.get("rpc.system"), Some(&"grpc".to_string()));
+    assert_eq!(
+        attributes.get("rpc.service"),
+        Some(&"grpc.health.v1.Health".to_string())
+    );
+    assert_eq!(attributes.get("rpc.method"), Some(&"Check".to_string()));
+}
@@ -0,0 +1,50 @@
+use otel_util::tonic::transport::{Endpoint, Server};
+use otel_util::{tracing, use_otel_at_test, TracePropagationInterceptor, TracePropagationLayer};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+
+// テスト対象の gRPC サービスの代わり。空いている port で起動し、その port を返す。
+async fn start_server() -> u16 {
+    let (_, health_service) = tonic_health::server::health_reporter();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(
+        Server::builder()
+            .layer(TracePropagationLayer)
+            .add_service(health_service)
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    port
+}
+
+#[tracing::instrument]
+async fn check_health(port: u16) -> i32 {
+    let channel = Endpoint::from_shared(format!("http://127.0.0.1:{}", port))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = HealthClient::with_interceptor(channel, TracePropagationInterceptor);
+    client
+        .check(HealthCheckRequest {
+            service: "".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .status
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14328")]
+async fn grpc_propagation_otel_test() {
+    // given
+    let port = start_server().await;
+
+    // when
+    let status = check_health(port).await;
+
+    // then
+    assert_eq!(status, ServingStatus::Serving as i32);
+}
@@ -0,0 +1,22 @@
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_guard_drop_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &[
+            "tests::guard_drop_test_case::sync_guard_drop_test",
+            "tests::guard_drop_test_case::block_on_guard_drop_test",
+        ],
+        14337,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec!["block_on_guard_drop_span", "sync_guard_drop_span"]
+    );
+}
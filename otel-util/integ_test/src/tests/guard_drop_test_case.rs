@@ -0,0 +1,26 @@
+use otel_util::tracing::{self, Instrument};
+use otel_util::{futures, DefaultBatchOtelGuardFactory};
+use std::time::Duration;
+
+// force_flush を呼ばずに guard を drop しても、drop の中で残りの span が送信される。
+#[test]
+fn sync_guard_drop_test() {
+    let _guard =
+        DefaultBatchOtelGuardFactory::new("grpc://localhost:14337", "guard_drop_test", "0.1.0")
+            .with_shutdown_timeout(Duration::from_secs(10))
+            .build();
+    tracing::info_span!("sync_guard_drop_span").in_scope(|| tracing::info!("in sync test"));
+}
+
+// tokio 以外の executor 上で drop しても、送信は専用のスレッドで行われる。
+#[test]
+fn block_on_guard_drop_test() {
+    futures::executor::block_on(async {
+        let _guard =
+            DefaultBatchOtelGuardFactory::new("grpc://localhost:14337", "guard_drop_test", "0.1.0")
+                .build();
+        async { tracing::info!("in block_on") }
+            .instrument(tracing::info_span!("block_on_guard_drop_span"))
+            .await;
+    });
+}
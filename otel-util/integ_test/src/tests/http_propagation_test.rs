@@ -0,0 +1,77 @@
+use super::integ_test_executor::InProcessTestExecutor;
+use otel_util::http::header::{HeaderMap, HeaderValue};
+use otel_util::opentelemetry::propagation::Extractor;
+use otel_util::PropagationContext;
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn check_otlp_output_http_propagation_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &["tests::http_propagation_test_case::http_propagation_otel_test"],
+        14325,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec![
+            "call_service",
+            "handle_request",
+            "http_propagation_otel_test"
+        ]
+    );
+    // サーバー側の root span は、リクエストを送った span の子になる。
+    assert_eq!(
+        result.get_parent_span_name("handle_request"),
+        Some("call_service".to_string())
+    );
+}
+
+#[test]
+fn header_map_to_propagation_context() {
+    // given
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "TraceParent",
+        HeaderValue::from_static("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+    );
+    headers.append("tracestate", HeaderValue::from_static("a=1"));
+    headers.append("tracestate", HeaderValue::from_static("b=2"));
+
+    // when
+    let context = PropagationContext::from(&headers);
+
+    // then
+    assert_eq!(
+        context.get("traceparent"),
+        Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+    );
+    assert_eq!(context.get("tracestate"), Some("a=1,b=2"));
+}
+
+#[test]
+fn propagation_context_to_header_map() {
+    // given
+    let mut headers = HeaderMap::new();
+    headers.insert("baggage", HeaderValue::from_static("stale=1"));
+    let context = PropagationContext(HashMap::from([
+        ("Baggage".to_string(), "tenant.id=tenant-1".to_string()),
+        ("invalid header".to_string(), "ignored".to_string()),
+    ]));
+
+    // when
+    context.inject_into_headers(&mut headers);
+
+    // then
+    assert_eq!(headers.len(), 1);
+    assert_eq!(
+        headers.get("baggage"),
+        Some(&HeaderValue::from_static("tenant.id=tenant-1"))
+    );
+    // key の大文字小文字は区別せずに取り出せる。
+    assert_eq!(context.get("baggage"), Some("tenant.id=tenant-1"));
+}
@@ -0,0 +1,56 @@
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use otel_util::reqwest;
+use otel_util::reqwest_middleware::ClientBuilder;
+use otel_util::tracing::{self, Instrument};
+use otel_util::tracing_opentelemetry::OpenTelemetrySpanExt;
+use otel_util::{use_otel_at_test, PropagationContext, TracePropagationMiddleware};
+use std::convert::Infallible;
+
+// テスト対象の HTTP サービスの代わり。ヘッダーから context を取り出し、root span の親にする。
+async fn handle(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let span = tracing::info_span!(parent: None, "handle_request");
+    span.set_parent(PropagationContext::from(req.headers()).extract());
+    async {
+        tracing::info!("request handled");
+    }
+    .instrument(span)
+    .await;
+    Ok(Response::new(Body::from("ok")))
+}
+
+// 空いている port で起動し、その port を返す。
+fn start_server() -> u16 {
+    let make_service = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(handle)) });
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+    let port = server.local_addr().port();
+    tokio::spawn(server);
+    port
+}
+
+#[tracing::instrument]
+async fn call_service(port: u16) -> String {
+    let client = ClientBuilder::new(reqwest::Client::new())
+        .with(TracePropagationMiddleware)
+        .build();
+    client
+        .get(format!("http://127.0.0.1:{}/", port))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap()
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14325")]
+async fn http_propagation_otel_test() {
+    // given
+    let port = start_server();
+
+    // when
+    let body = call_service(port).await;
+
+    // then
+    assert_eq!(body, "ok");
+}
@@ -0,0 +1,125 @@
+use super::trace_contents::TraceContent;
+use opentelemetry_proto::tonic::collector::trace::v1::{
+    trace_service_server::{TraceService, TraceServiceServer},
+    ExportTraceServiceRequest, ExportTraceServiceResponse,
+};
+use opentelemetry_proto::tonic::trace::v1::ResourceSpans;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tonic::codec::CompressionEncoding;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+
+#[derive(Clone, Default)]
+struct ReceivedSpans {
+    spans: Arc<Mutex<Vec<ResourceSpans>>>,
+    headers: Arc<Mutex<Vec<HashMap<String, String>>>>,
+}
+
+#[tonic::async_trait]
+impl TraceService for ReceivedSpans {
+    async fn export(
+        &self,
+        request: tonic::Request<ExportTraceServiceRequest>,
+    ) -> Result<tonic::Response<ExportTraceServiceResponse>, tonic::Status> {
+        self.headers.lock().unwrap().push(
+            request
+                .metadata()
+                .clone()
+                .into_headers()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string(),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )
+                })
+                .collect(),
+        );
+        self.spans
+            .lock()
+            .unwrap()
+            .extend(request.into_inner().resource_spans);
+        Ok(tonic::Response::new(ExportTraceServiceResponse {
+            partial_success: None,
+        }))
+    }
+}
+
+// docker の collector を使わずに、OTLP/gRPC で送信された trace をメモリ上で受け取る collector。
+pub struct InProcessCollector {
+    received: ReceivedSpans,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl InProcessCollector {
+    pub async fn start(port: u16) -> Self {
+        Self::start_with(port, None).await
+    }
+
+    // certs/ の証明書で TLS を有効にし、gzip で圧縮された trace も受け取る。
+    // client_auth が true の場合は、certs/ca.pem で署名されたクライアント証明書を要求する。
+    pub async fn start_tls(port: u16, client_auth: bool) -> Self {
+        let cert = |name: &str| {
+            std::fs::read(format!("{}/certs/{}", env!("CARGO_MANIFEST_DIR"), name))
+                .expect("Failed to read test certificate")
+        };
+        let mut tls_config = ServerTlsConfig::new()
+            .identity(Identity::from_pem(cert("server.pem"), cert("server.key")));
+        if client_auth {
+            tls_config = tls_config.client_ca_root(Certificate::from_pem(cert("ca.pem")));
+        }
+        Self::start_with(port, Some(tls_config)).await
+    }
+
+    async fn start_with(port: u16, tls_config: Option<ServerTlsConfig>) -> Self {
+        let received = ReceivedSpans::default();
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .expect("Failed to bind in-process collector");
+        let incoming = TcpIncoming::from_listener(listener, true, None)
+            .expect("Failed to accept connections for in-process collector");
+        let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+
+        let mut server = tonic::transport::Server::builder();
+        if let Some(tls_config) = tls_config {
+            server = server
+                .tls_config(tls_config)
+                .expect("Failed to configure TLS for in-process collector");
+        }
+        tokio::spawn(
+            server
+                .add_service(
+                    TraceServiceServer::new(received.clone())
+                        .accept_compressed(CompressionEncoding::Gzip),
+                )
+                .serve_with_incoming_shutdown(incoming, async {
+                    shutdown_signal.await.ok();
+                }),
+        );
+
+        InProcessCollector {
+            received,
+            shutdown: Some(shutdown),
+        }
+    }
+
+    pub fn trace_content(&self) -> TraceContent {
+        TraceContent::new(self.received.spans.lock().unwrap().clone())
+    }
+
+    // export のリクエストごとの gRPC metadata。
+    pub fn received_headers(&self) -> Vec<HashMap<String, String>> {
+        self.received.headers.lock().unwrap().clone()
+    }
+}
+
+impl Drop for InProcessCollector {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
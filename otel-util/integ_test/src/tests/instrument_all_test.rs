@@ -0,0 +1,47 @@
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_instrument_all_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &["tests::instrument_all_test_case::instrument_all_otel_test"],
+        14322,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec![
+            "add",
+            "add_async",
+            "add_err",
+            "instrument_all_otel_test",
+            "login",
+            "multiply",
+            "query"
+        ]
+    );
+
+    let login = result.get_span_attributes("login");
+    assert_eq!(login.get("user"), Some(&"user".to_string()));
+    assert_eq!(login.get("password"), None);
+
+    let query = result.get_span_attributes("query");
+    assert_eq!(query.get("a"), Some(&"10".to_string()));
+    assert_eq!(query.get("_connection"), None);
+
+    let event_exceptions = result.get_span_event_exceptions();
+    assert_eq!(
+        event_exceptions.get("add_err"),
+        Some(&vec!["some error at add_err".to_string()])
+    );
+
+    // ret の event は message を持たないため、event 名は空文字になる。
+    let event_names = result.get_span_event_names();
+    assert_eq!(event_names.get("add"), Some(&vec!["".to_string()]));
+    assert_eq!(event_names.get("add_async"), Some(&vec!["".to_string()]));
+    assert_eq!(event_names.get("multiply"), Some(&vec![]));
+}
@@ -0,0 +1,69 @@
+use anyhow::{anyhow, Result};
+use otel_util::{instrument_all, tracing, use_otel_at_test};
+
+#[instrument_all(
+    skip_fns(not_traced),
+    skip_args(password),
+    skip_arg_types(Connection),
+    ret
+)]
+mod calculator {
+    use super::*;
+
+    // Debug を実装していないため、skip_arg_types で記録対象から外す。
+    pub struct Connection;
+
+    pub fn add(a: u64, b: u64) -> Result<u64> {
+        Ok(a + b)
+    }
+
+    pub fn add_err(_a: u64, _b: u64) -> Result<u64> {
+        Err(anyhow!("some error at add_err"))
+    }
+
+    pub async fn add_async(a: u64, b: u64) -> u64 {
+        a + b
+    }
+
+    pub fn login(user: &str, password: &str) -> bool {
+        !user.is_empty() && !password.is_empty()
+    }
+
+    pub fn query(_connection: &Connection, a: u64) -> u64 {
+        a
+    }
+
+    pub fn not_traced() {}
+}
+
+// Debug を実装していない self は記録されない。
+pub struct Multiplier {
+    base: u64,
+}
+
+#[instrument_all(level = "info")]
+impl Multiplier {
+    pub fn multiply(&self, a: u64) -> u64 {
+        self.base * a
+    }
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14322")]
+async fn instrument_all_otel_test() {
+    // when
+    let sum = calculator::add(10, 20).unwrap();
+    let err = calculator::add_err(10, 20);
+    let async_sum = calculator::add_async(10, 20).await;
+    let logged_in = calculator::login("user", "secret");
+    let queried = calculator::query(&calculator::Connection, 10);
+    calculator::not_traced();
+    let product = Multiplier { base: 2 }.multiply(10);
+
+    // then
+    assert_eq!(30, sum);
+    assert!(err.is_err());
+    assert_eq!(30, async_sum);
+    assert!(logged_in);
+    assert_eq!(10, queried);
+    assert_eq!(20, product);
+}
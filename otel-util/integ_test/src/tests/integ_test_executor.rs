@@ -2,6 +2,9 @@ const CONTAINER_RESULT_PATH: &str = "/result.json";
 
 use std::{os::unix::fs::PermissionsExt, vec};
 
+use super::in_process_collector::InProcessCollector;
+use super::trace_contents::TraceContent;
+
 use testcontainers::{
     core::{AccessMode, IntoContainerPort, Mount},
     runners::AsyncRunner,
@@ -87,3 +90,35 @@ impl OriginalTestExecutor {
         result_path
     }
 }
+
+// docker を使わずに、InProcessCollector で trace を受信しながらテストを実行する。
+// 実行するテストの endpoint は、grpc://localhost:{port} としておくこと。
+// global な tracer を共有しないよう、テストは 1 つずつ別プロセスの cargo test で実行する。
+pub struct InProcessTestExecutor {
+    test_paths: Vec<String>,
+    port: u16,
+}
+
+impl InProcessTestExecutor {
+    pub fn new(test_paths: &[&str], port: u16) -> Self {
+        InProcessTestExecutor {
+            test_paths: test_paths.iter().map(|path| path.to_string()).collect(),
+            port,
+        }
+    }
+
+    pub async fn execute(&self) -> TraceContent {
+        let collector = InProcessCollector::start(self.port).await;
+
+        for test_path in &self.test_paths {
+            tokio::process::Command::new("cargo")
+                .arg("test")
+                .arg(test_path)
+                .output()
+                .await
+                .expect("Failed to execute cargo test");
+        }
+
+        collector.trace_content()
+    }
+}
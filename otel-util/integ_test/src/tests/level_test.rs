@@ -0,0 +1,74 @@
+use super::in_process_collector::InProcessCollector;
+
+async fn run_test_case(name: &str, envs: &[(&str, &str)]) -> String {
+    let output = tokio::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            &format!("tests::level_test_case::{}", name),
+            "--exact",
+            "--nocapture",
+        ])
+        .env_remove("RUST_LOG")
+        .env_remove("OTEL_TEST_LOG")
+        .envs(envs.iter().copied())
+        .output()
+        .await
+        .expect("Failed to execute test");
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[tokio::test]
+async fn check_otlp_output_level_otel_test() {
+    // given
+    let collector = InProcessCollector::start(14346).await;
+
+    // when
+    run_test_case("debug_level_otel_test", &[]).await;
+    run_test_case("default_level_otel_test", &[]).await;
+    let env_level_stdout = run_test_case(
+        "env_level_otel_test",
+        &[("OTEL_TEST_LOG", "debug"), ("RUST_LOG", "warn")],
+    )
+    .await;
+    run_test_case("target_level_otel_test", &[]).await;
+    // 2 つのテストを同じプロセスで並行に実行する。
+    tokio::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "tests::level_test_case::concurrent_",
+            "--ignored",
+            "--test-threads=2",
+            "--nocapture",
+        ])
+        .env_remove("RUST_LOG")
+        .env_remove("OTEL_TEST_LOG")
+        .output()
+        .await
+        .expect("Failed to execute test");
+
+    // then
+    let result = collector.trace_content();
+    let span_names = result.get_span_names();
+    // level = "debug" の場合は debug の span まで送信される。
+    assert!(span_names.contains(&"debug_span".to_string()));
+    assert!(!span_names.contains(&"trace_span".to_string()));
+    // 既定は info。
+    assert!(span_names.contains(&"default_level_otel_test".to_string()));
+    assert!(!span_names.contains(&"default_level_debug_span".to_string()));
+
+    // OTEL_TEST_LOG は trace に、RUST_LOG は標準出力に適用される。
+    assert!(span_names.contains(&"env_level_debug_span".to_string()));
+    assert!(result.get_span_event_names()["env_level_debug_span"]
+        .contains(&"env level info event".to_string()));
+    assert!(
+        !env_level_stdout.contains("env level info event"),
+        "{}",
+        env_level_stdout
+    );
+
+    // target ごとに level を指定できる。
+    assert!(span_names.contains(&"quiet_span".to_string()));
+    assert!(!span_names.contains(&"noisy_span".to_string()));
+
+    // level はプロセス全体ではなくテストごとに適用される。
+    assert!(span_names.contains(&"concurrent_debug_level_debug_span".to_string()));
+    assert!(!span_names.contains(&"concurrent_info_level_debug_span".to_string()));
+}
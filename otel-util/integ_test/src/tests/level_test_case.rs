@@ -0,0 +1,77 @@
+use otel_util::{tracing, use_otel_at_test};
+use std::sync::Barrier;
+
+#[tracing::instrument(level = "debug")]
+fn debug_span() {}
+
+#[tracing::instrument(level = "trace")]
+fn trace_span() {}
+
+#[tracing::instrument(level = "debug")]
+fn default_level_debug_span() {}
+
+#[tracing::instrument(level = "debug")]
+fn env_level_debug_span() {
+    tracing::info!("env level info event");
+}
+
+mod noisy {
+    use otel_util::tracing;
+
+    #[tracing::instrument]
+    pub fn noisy_span() {}
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14346", level = "debug")]
+async fn debug_level_otel_test() {
+    debug_span();
+    trace_span();
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14346")]
+async fn default_level_otel_test() {
+    default_level_debug_span();
+}
+
+// OTEL_TEST_LOG=debug, RUST_LOG=warn で実行する。
+#[use_otel_at_test(endpoint = "grpc://localhost:14346")]
+async fn env_level_otel_test() {
+    env_level_debug_span();
+}
+
+#[use_otel_at_test(
+    endpoint = "grpc://localhost:14346",
+    level = "info,integ_test::tests::level_test_case::noisy=off"
+)]
+async fn target_level_otel_test() {
+    noisy::noisy_span();
+    tracing::info_span!("quiet_span").in_scope(|| {});
+}
+
+// concurrent_ で始まる 2 つのテストは、同じプロセスで並行に実行する。
+// 両方の guard を作った後で span を記録し、それぞれのテストの level が適用されることを確認する。
+// 互いを待つため、2 つを同時に実行できない通常の cargo test では実行しない。
+static CONCURRENT_GUARDS_BUILT: Barrier = Barrier::new(2);
+static CONCURRENT_SPANS_RECORDED: Barrier = Barrier::new(2);
+
+#[tracing::instrument(level = "debug")]
+fn concurrent_debug_level_debug_span() {}
+
+#[tracing::instrument(level = "debug")]
+fn concurrent_info_level_debug_span() {}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14346", level = "debug")]
+#[ignore = "run by check_otlp_output_level_otel_test with --test-threads=2"]
+async fn concurrent_debug_level_otel_test() {
+    CONCURRENT_GUARDS_BUILT.wait();
+    concurrent_debug_level_debug_span();
+    CONCURRENT_SPANS_RECORDED.wait();
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14346", level = "info")]
+#[ignore = "run by check_otlp_output_level_otel_test with --test-threads=2"]
+async fn concurrent_info_level_otel_test() {
+    CONCURRENT_GUARDS_BUILT.wait();
+    concurrent_info_level_debug_span();
+    CONCURRENT_SPANS_RECORDED.wait();
+}
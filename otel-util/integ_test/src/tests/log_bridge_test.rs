@@ -0,0 +1,23 @@
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_log_bridge_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &["tests::log_bridge_test_case::log_bridge_otel_test"],
+        14345,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    // log crate の record は、実行中のテストの span の event として記録される。
+    let events = &result.get_span_event_names()["log_bridge_otel_test"];
+    assert!(events.contains(&"connecting".to_string()));
+    assert!(events.contains(&"connection pool is exhausted".to_string()));
+    // target ごとの level より低い record は記録されない。
+    assert!(!events.contains(&"pool stats".to_string()));
+    assert!(!events.contains(&"noisy warning".to_string()));
+    assert!(events.contains(&"noisy error".to_string()));
+}
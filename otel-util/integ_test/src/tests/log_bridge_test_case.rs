@@ -0,0 +1,20 @@
+use otel_util::use_otel_at_test;
+
+// log crate で出力する依存ライブラリの代わり。
+mod legacy_lib {
+    pub fn connect() {
+        log::info!("connecting");
+        log::warn!(target: "legacy_lib::pool", "connection pool is exhausted");
+        log::debug!(target: "legacy_lib::pool", "pool stats");
+        log::warn!(target: "noisy_lib", "noisy warning");
+        log::error!(target: "noisy_lib", "noisy error");
+    }
+}
+
+#[use_otel_at_test(
+    endpoint = "grpc://localhost:14345",
+    log_bridge = "info,noisy_lib=error"
+)]
+async fn log_bridge_otel_test() {
+    legacy_lib::connect();
+}
@@ -0,0 +1,12 @@
+// use_otel_at_test の引数・関数シグネチャの検証結果を、コンパイルエラーの内容で確認する。
+#[test]
+fn use_otel_at_test_rejects_invalid_usage() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("ui/use_otel_at_test/fail/*.rs");
+}
+
+#[test]
+fn use_otel_at_test_accepts_tokio_test_arguments() {
+    let t = trybuild::TestCases::new();
+    t.pass("ui/use_otel_at_test/pass/*.rs");
+}
@@ -0,0 +1,38 @@
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_module_otel_tests() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &[
+            "tests::module_test_case::instrumented::async_otel_test",
+            "tests::module_test_case::instrumented::multi_thread_otel_test",
+            "tests::module_test_case::instrumented::sync_otel_test",
+        ],
+        14321,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec![
+            "async_otel_test",
+            "multi_thread_otel_test",
+            "sample_add",
+            "sample_add",
+            "sample_add",
+            "sync_otel_test"
+        ]
+    );
+    assert_eq!(
+        result.get_service_names(),
+        vec![
+            "module_test_case::async_otel_test",
+            "module_test_case::multi_thread_otel_test",
+            "module_test_case::sync_otel_test"
+        ]
+    );
+}
@@ -0,0 +1,33 @@
+use otel_util::otel_tests;
+
+#[otel_tests(
+    endpoint = "grpc://localhost:14321",
+    service_name_prefix = "module_test_case::",
+    sync_tests = true
+)]
+mod instrumented {
+    use otel_util::tracing;
+
+    #[tracing::instrument]
+    fn sample_add(a: u64, b: u64) -> u64 {
+        a + b
+    }
+
+    #[tokio::test]
+    async fn async_otel_test() {
+        let c = sample_add(10, 20);
+        assert_eq!(30, c);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn multi_thread_otel_test() {
+        let c = sample_add(10, 20);
+        assert_eq!(30, c);
+    }
+
+    #[test]
+    fn sync_otel_test() {
+        let c = sample_add(10, 20);
+        assert_eq!(30, c);
+    }
+}
@@ -0,0 +1,104 @@
+use super::in_process_collector::InProcessCollector;
+
+async fn run_test_case(name: &str, envs: &[(&str, &str)]) -> std::process::Output {
+    tokio::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            &format!("tests::otlp_tls_test_case::{}", name),
+            "--exact",
+            "--nocapture",
+        ])
+        .envs(envs.iter().copied())
+        .output()
+        .await
+        .expect("Failed to execute test")
+}
+
+#[tokio::test]
+async fn check_otlp_output_tls_otel_test() {
+    // given
+    let collector = InProcessCollector::start_tls(14338, false).await;
+
+    // when
+    run_test_case("tls_otel_test", &[("OTEL_UTIL_TEST_TOKEN", "secret")]).await;
+
+    // then
+    assert!(collector
+        .trace_content()
+        .get_span_names()
+        .contains(&"tls_otel_test".to_string()));
+    let headers = collector.received_headers();
+    assert!(
+        headers.iter().any(|headers| {
+            headers.get("authorization").map(String::as_str) == Some("Bearer secret")
+                && headers.get("x-tenant").map(String::as_str) == Some("ci")
+                && headers.get("grpc-encoding").map(String::as_str) == Some("gzip")
+        }),
+        "{:?}",
+        headers
+    );
+}
+
+#[tokio::test]
+async fn check_otlp_output_mutual_tls_otel_test() {
+    // given
+    let collector = InProcessCollector::start_tls(14339, true).await;
+
+    // when
+    run_test_case("mutual_tls_otel_test", &[]).await;
+
+    // then
+    assert!(collector
+        .trace_content()
+        .get_span_names()
+        .contains(&"mutual_tls_otel_test".to_string()));
+}
+
+#[tokio::test]
+async fn check_otlp_output_tls_from_env_otel_test() {
+    // given
+    let collector = InProcessCollector::start_tls(14340, false).await;
+
+    // when
+    run_test_case(
+        "tls_from_env_otel_test",
+        &[
+            ("OTEL_TEST_OTLP_CA_CERT", "certs/ca.pem"),
+            (
+                "OTEL_TEST_OTLP_HEADERS",
+                "x-api-key=${OTEL_UTIL_TEST_TOKEN}",
+            ),
+            ("OTEL_TEST_OTLP_COMPRESSION", "gzip"),
+            ("OTEL_UTIL_TEST_TOKEN", "secret"),
+        ],
+    )
+    .await;
+
+    // then
+    assert!(collector
+        .trace_content()
+        .get_span_names()
+        .contains(&"tls_from_env_otel_test".to_string()));
+    let headers = collector.received_headers();
+    assert!(
+        headers.iter().any(|headers| {
+            headers.get("x-api-key").map(String::as_str) == Some("secret")
+                && headers.get("grpc-encoding").map(String::as_str) == Some("gzip")
+        }),
+        "{:?}",
+        headers
+    );
+}
+
+#[tokio::test]
+async fn check_export_error_missing_client_cert_otel_test() {
+    // given
+    let collector = InProcessCollector::start_tls(14341, true).await;
+
+    // when
+    let output = run_test_case("missing_client_cert_otel_test", &[]).await;
+
+    // then
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("otel-util: failed to export"), "{}", stderr);
+    assert!(collector.trace_content().get_span_names().is_empty());
+}
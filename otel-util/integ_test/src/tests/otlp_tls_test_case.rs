@@ -0,0 +1,27 @@
+use otel_util::use_otel_at_test;
+
+// certs/ のパスは、cargo test の実行時のカレントディレクトリ (integ_test) からの相対パス。
+#[use_otel_at_test(
+    endpoint = "https://localhost:14338",
+    tls_ca = "certs/ca.pem",
+    headers = "authorization=Bearer ${OTEL_UTIL_TEST_TOKEN},x-tenant=ci",
+    compression = "gzip",
+    export_timeout_ms = 3000
+)]
+async fn tls_otel_test() {}
+
+#[use_otel_at_test(
+    endpoint = "https://localhost:14339",
+    tls_ca = "certs/ca.pem",
+    tls_client_cert = "certs/client.pem",
+    tls_client_key = "certs/client.key"
+)]
+async fn mutual_tls_otel_test() {}
+
+// 接続設定は OTEL_TEST_OTLP_* 環境変数で与える。
+#[use_otel_at_test(endpoint = "https://localhost:14340")]
+async fn tls_from_env_otel_test() {}
+
+// クライアント証明書を要求する collector に、証明書なしで接続する。
+#[use_otel_at_test(endpoint = "https://localhost:14341", tls_ca = "certs/ca.pem")]
+async fn missing_client_cert_otel_test() {}
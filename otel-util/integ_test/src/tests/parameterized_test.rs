@@ -0,0 +1,47 @@
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_parameterized_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &[
+            "tests::parameterized_test_case::add_otel_test::case_1",
+            "tests::parameterized_test_case::add_otel_test::case_2",
+            "tests::parameterized_test_case::add_otel_test::case_3",
+        ],
+        14320,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec![
+            "add_otel_test::case_1",
+            "add_otel_test::case_2",
+            "add_otel_test::case_3",
+            "sample_add",
+            "sample_add",
+            "sample_add",
+            "sample_add"
+        ]
+    );
+
+    let case_1 = result.get_span_attributes("add_otel_test::case_1");
+    assert_eq!(case_1.get("test.case.a"), Some(&"10".to_string()));
+    assert_eq!(case_1.get("test.case.b"), Some(&"20".to_string()));
+    assert_eq!(case_1.get("test.case.expected"), Some(&"30".to_string()));
+
+    let case_2 = result.get_span_attributes("add_otel_test::case_2");
+    assert_eq!(case_2.get("test.case.a"), Some(&"1".to_string()));
+    assert_eq!(case_2.get("test.case.b"), Some(&"2".to_string()));
+    assert_eq!(case_2.get("test.case.expected"), Some(&"3".to_string()));
+
+    // 定数や式の引数は、ソースではなく評価した値を記録する。
+    let case_3 = result.get_span_attributes("add_otel_test::case_3");
+    assert_eq!(case_3.get("test.case.a"), Some(&"3".to_string()));
+    assert_eq!(case_3.get("test.case.b"), Some(&"6".to_string()));
+    assert_eq!(case_3.get("test.case.expected"), Some(&"9".to_string()));
+}
@@ -0,0 +1,20 @@
+use otel_util::{tracing, use_otel_at_test};
+
+#[tracing::instrument]
+fn sample_add(a: u64, b: u64) -> u64 {
+    a + b
+}
+
+const N: u64 = 3;
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14320")]
+#[case(10, 20, 30)]
+#[case(1, 2, 3)]
+#[case(N, N * 2, sample_add(N, N * 2))]
+async fn add_otel_test(a: u64, b: u64, expected: u64) {
+    // when
+    let c = sample_add(a, b);
+
+    // then
+    assert_eq!(expected, c);
+}
@@ -0,0 +1,47 @@
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_process_propagation_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &["tests::process_propagation_test_case::parent_process_otel_test"],
+        14324,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_service_names(),
+        vec!["child_process_otel_test", "parent_process_otel_test"]
+    );
+    assert_eq!(
+        result.get_span_names(),
+        vec![
+            "child_process_otel_test",
+            "linked_consumer",
+            "parent_process_otel_test",
+            "spawn_child_process"
+        ]
+    );
+    // 子プロセスの root span は、子プロセスを起動した span の子になる。
+    assert_eq!(
+        result.get_parent_span_name("child_process_otel_test"),
+        Some("spawn_child_process".to_string())
+    );
+    assert_eq!(
+        result.get_parent_span_name("spawn_child_process"),
+        Some("parent_process_otel_test".to_string())
+    );
+    // 明示的に root とした span は新しい trace になり、link だけで子プロセスの span とつながる。
+    assert_eq!(result.get_parent_span_name("linked_consumer"), None);
+    assert_ne!(
+        result.get_trace_id("linked_consumer"),
+        result.get_trace_id("parent_process_otel_test")
+    );
+    assert_eq!(
+        result.get_linked_span_names("linked_consumer"),
+        vec!["child_process_otel_test"]
+    );
+}
@@ -0,0 +1,32 @@
+use otel_util::tracing;
+use otel_util::use_otel_at_test;
+use otel_util::PropagateTraceContext;
+use otel_util::Traced;
+
+#[tracing::instrument]
+async fn spawn_child_process() -> std::process::ExitStatus {
+    tokio::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "tests::process_propagation_test_case::child_process_otel_test",
+            "--exact",
+        ])
+        .propagate_trace_context()
+        .status()
+        .await
+        .expect("Failed to execute child process")
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14324")]
+async fn parent_process_otel_test() {
+    let status = spawn_child_process().await;
+    assert!(status.success());
+}
+
+// parent_process_otel_test から子プロセスとして実行される。
+#[use_otel_at_test(endpoint = "grpc://localhost:14324")]
+async fn child_process_otel_test() {
+    tracing::info!("running in child process");
+    // parent: None の span は、TRACEPARENT があっても親プロセスの trace には入らない。
+    let (_, span) = Traced::new(()).into_inner_with_linked_span("linked_consumer");
+    span.in_scope(|| tracing::info!("consumed"));
+}
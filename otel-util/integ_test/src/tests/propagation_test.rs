@@ -0,0 +1,152 @@
+use otel_util::opentelemetry::baggage::BaggageExt;
+use otel_util::opentelemetry::trace::{
+    SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+};
+use otel_util::opentelemetry::{Context, KeyValue};
+use otel_util::{composite_propagator, PropagationContext, PropagatorKind};
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const SPAN_ID: &str = "00f067aa0ba902b7";
+
+fn sample_context() -> Context {
+    let span_context = SpanContext::new(
+        TraceId::from_hex(TRACE_ID).unwrap(),
+        SpanId::from_hex(SPAN_ID).unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    Context::new()
+        .with_remote_span_context(span_context)
+        .with_baggage(vec![KeyValue::new("tenant.id", "tenant-1")])
+}
+
+// inject した PropagationContext を serde で送受信し、extract するまでを行う。
+fn round_trip(kinds: &[PropagatorKind]) -> (PropagationContext, Context) {
+    let propagator = composite_propagator(kinds);
+    let injected = PropagationContext::inject_with(&propagator, &sample_context());
+    let json = serde_json::to_string(&injected).unwrap();
+    let received: PropagationContext = serde_json::from_str(&json).unwrap();
+    let extracted = received.extract_with(&propagator);
+    (injected, extracted)
+}
+
+fn assert_span_context(context: &Context) {
+    let span = context.span();
+    let span_context = span.span_context();
+    assert_eq!(span_context.trace_id().to_string(), TRACE_ID);
+    assert_eq!(span_context.span_id().to_string(), SPAN_ID);
+    assert!(span_context.is_sampled());
+    assert!(span_context.is_remote());
+}
+
+fn tenant_id(context: &Context) -> Option<String> {
+    context.baggage().get("tenant.id").map(|v| v.to_string())
+}
+
+#[test]
+fn round_trip_trace_context() {
+    // when
+    let (injected, extracted) = round_trip(&[PropagatorKind::TraceContext]);
+
+    // then
+    assert_eq!(
+        injected.0.get("traceparent"),
+        Some(&format!("00-{}-{}-01", TRACE_ID, SPAN_ID))
+    );
+    assert_span_context(&extracted);
+    assert_eq!(tenant_id(&extracted), None);
+}
+
+#[test]
+fn round_trip_baggage() {
+    // when
+    let (injected, extracted) = round_trip(&[PropagatorKind::Baggage]);
+
+    // then
+    assert_eq!(
+        injected.0.get("baggage"),
+        Some(&"tenant.id=tenant-1".to_string())
+    );
+    assert_eq!(tenant_id(&extracted), Some("tenant-1".to_string()));
+}
+
+#[test]
+fn round_trip_b3_single_header() {
+    // when
+    let (injected, extracted) = round_trip(&[PropagatorKind::B3]);
+
+    // then
+    assert_eq!(
+        injected.0.get("b3"),
+        Some(&format!("{}-{}-1", TRACE_ID, SPAN_ID))
+    );
+    assert_eq!(injected.0.get("x-b3-traceid"), None);
+    assert_span_context(&extracted);
+}
+
+#[test]
+fn round_trip_b3_multi_header() {
+    // when
+    let (injected, extracted) = round_trip(&[PropagatorKind::B3Multi]);
+
+    // then
+    assert_eq!(injected.0.get("x-b3-traceid"), Some(&TRACE_ID.to_string()));
+    assert_eq!(injected.0.get("x-b3-spanid"), Some(&SPAN_ID.to_string()));
+    assert_eq!(injected.0.get("x-b3-sampled"), Some(&"1".to_string()));
+    assert_eq!(injected.0.get("b3"), None);
+    assert_span_context(&extracted);
+}
+
+#[test]
+fn round_trip_jaeger() {
+    // when
+    let (injected, extracted) = round_trip(&[PropagatorKind::Jaeger]);
+
+    // then
+    assert_eq!(
+        injected.0.get("uber-trace-id"),
+        Some(&format!("{}:{}:0:1", TRACE_ID, SPAN_ID))
+    );
+    assert_span_context(&extracted);
+}
+
+#[test]
+fn round_trip_composite() {
+    // when
+    let (injected, extracted) = round_trip(&[
+        PropagatorKind::TraceContext,
+        PropagatorKind::Baggage,
+        PropagatorKind::B3,
+        PropagatorKind::B3Multi,
+        PropagatorKind::Jaeger,
+    ]);
+
+    // then
+    for key in [
+        "traceparent",
+        "baggage",
+        "b3",
+        "x-b3-traceid",
+        "x-b3-spanid",
+        "uber-trace-id",
+    ] {
+        assert!(injected.0.contains_key(key), "`{}` is not injected", key);
+    }
+    assert_span_context(&extracted);
+    assert_eq!(tenant_id(&extracted), Some("tenant-1".to_string()));
+}
+
+#[test]
+fn parse_propagator_list() {
+    assert_eq!(
+        PropagatorKind::parse_list("tracecontext, baggage,b3multi"),
+        Ok(vec![
+            PropagatorKind::TraceContext,
+            PropagatorKind::Baggage,
+            PropagatorKind::B3Multi
+        ])
+    );
+    assert_eq!(PropagatorKind::parse_list("none"), Ok(vec![]));
+    assert!(PropagatorKind::parse_list("tracecontext,xray").is_err());
+}
@@ -0,0 +1,51 @@
+use super::in_process_collector::InProcessCollector;
+
+#[tokio::test]
+async fn check_otlp_output_resource_otel_test() {
+    // given
+    let collector = InProcessCollector::start(14334).await;
+    let commit_id = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .expect("Failed to execute git");
+    let commit_id = String::from_utf8(commit_id.stdout).unwrap();
+
+    // when
+    // GitHub Actions 上で実行された場合を再現する。
+    tokio::process::Command::new("cargo")
+        .arg("test")
+        .arg("tests::resource_test_case::resource_otel_test")
+        .env("GITHUB_ACTIONS", "true")
+        .env("GITHUB_WORKFLOW", "ci")
+        .env("GITHUB_RUN_ID", "42")
+        .env("GITHUB_JOB", "test")
+        .env("GITHUB_SERVER_URL", "https://github.com")
+        .env("GITHUB_REPOSITORY", "example/crate")
+        .env("GITHUB_HEAD_REF", "feature/resource")
+        .env_remove("GITHUB_SHA")
+        .env_remove("RUNNER_NAME")
+        .output()
+        .await
+        .expect("Failed to execute cargo test");
+
+    // then
+    let attributes = collector.trace_content().get_resource_attributes();
+    assert_eq!(attributes["service.name"], "resource_otel_test");
+    assert_eq!(attributes["vcs.commit.id"], commit_id.trim());
+    assert_eq!(attributes["vcs.branch.name"], "feature/resource");
+    assert_eq!(attributes["cicd.provider.name"], "github_actions");
+    assert_eq!(attributes["cicd.pipeline.name"], "ci");
+    assert_eq!(attributes["cicd.pipeline.run.id"], "42");
+    assert_eq!(attributes["cicd.pipeline.task.name"], "test");
+    assert_eq!(
+        attributes["cicd.pipeline.run.url.full"],
+        "https://github.com/example/crate/actions/runs/42"
+    );
+    assert_eq!(attributes["os.type"], std::env::consts::OS);
+    assert!(attributes.contains_key("host.name"));
+    assert!(attributes.contains_key("process.pid"));
+    assert!(attributes.contains_key("process.command_args"));
+    assert_eq!(attributes["process.runtime.name"], "rustc");
+    assert!(attributes["process.runtime.description"].starts_with("rustc "));
+    assert_eq!(attributes["rust.build.profile"], "debug");
+}
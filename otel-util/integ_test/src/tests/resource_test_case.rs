@@ -0,0 +1,4 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14334")]
+async fn resource_otel_test() {}
@@ -0,0 +1,55 @@
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_current_thread_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &["tests::runtime_flavor_test_case::current_thread_otel_test"],
+        14317,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec!["current_thread_otel_test", "sample_sleep_add"]
+    );
+}
+
+#[tokio::test]
+async fn check_otlp_output_multi_thread_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &["tests::runtime_flavor_test_case::multi_thread_otel_test"],
+        14318,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec!["multi_thread_otel_test", "sample_sleep_add"]
+    );
+}
+
+#[tokio::test]
+async fn check_otlp_output_start_paused_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &["tests::runtime_flavor_test_case::start_paused_otel_test"],
+        14319,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec!["sample_sleep_add", "start_paused_otel_test"]
+    );
+}
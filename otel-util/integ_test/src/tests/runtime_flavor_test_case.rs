@@ -0,0 +1,37 @@
+use otel_util::{tracing, use_otel_at_test};
+
+#[tracing::instrument]
+async fn sample_sleep_add(a: u64, b: u64) -> u64 {
+    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    a + b
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14317", flavor = "current_thread")]
+async fn current_thread_otel_test() {
+    let c = sample_sleep_add(10, 20).await;
+    assert_eq!(30, c);
+}
+
+#[use_otel_at_test(
+    endpoint = "grpc://localhost:14318",
+    flavor = "multi_thread",
+    worker_threads = 2
+)]
+async fn multi_thread_otel_test() {
+    // テスト本体は tokio::spawn され、テストのスレッドではなく worker スレッドで実行される。
+    let thread = std::thread::current();
+    assert!(
+        thread.name().is_some_and(|name| name.starts_with("tokio-")),
+        "{:?}",
+        thread.name()
+    );
+    let c = sample_sleep_add(10, 20).await;
+    assert_eq!(30, c);
+}
+
+// 仮想時間の sleep は即座に進むため、送信は実時間を必要としない専用 runtime で行われる必要がある。
+#[use_otel_at_test(endpoint = "grpc://localhost:14319", start_paused = true)]
+async fn start_paused_otel_test() {
+    let c = sample_sleep_add(10, 20).await;
+    assert_eq!(30, c);
+}
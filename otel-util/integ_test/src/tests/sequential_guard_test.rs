@@ -0,0 +1,38 @@
+use super::in_process_collector::InProcessCollector;
+
+#[tokio::test]
+async fn check_otlp_output_sequential_guard_otel_test() {
+    // given
+    let collector = InProcessCollector::start(14350).await;
+
+    // when
+    // 2 つのテストを、同じプロセスで 1 つずつ実行する。
+    let output = tokio::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "tests::sequential_guard_test_case::",
+            "--test-threads=1",
+            "--nocapture",
+        ])
+        .output()
+        .await
+        .expect("Failed to execute test");
+
+    // then
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    let result = collector.trace_content();
+    assert_eq!(
+        result.get_span_names(),
+        vec![
+            "a_first_otel_test",
+            "b_second_otel_test",
+            "first_work",
+            "second_work"
+        ]
+    );
+    // それぞれのテストの guard の resource で送信される。
+    assert_eq!(
+        result.get_service_names(),
+        vec!["a_first_otel_test", "b_second_otel_test"]
+    );
+}
@@ -0,0 +1,18 @@
+use otel_util::{tracing, use_otel_at_test};
+
+#[tracing::instrument]
+fn first_work() {}
+
+#[tracing::instrument]
+fn second_work() {}
+
+// 同じプロセスで順に実行される 2 つのテスト。先のテストの guard の drop が、後のテストの送信に影響しない。
+#[use_otel_at_test(endpoint = "grpc://localhost:14350")]
+async fn a_first_otel_test() {
+    first_work();
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14350")]
+async fn b_second_otel_test() {
+    second_work();
+}
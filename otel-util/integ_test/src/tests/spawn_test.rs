@@ -0,0 +1,64 @@
+use super::in_process_collector::InProcessCollector;
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_spawn_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(&["tests::spawn_test_case::spawn_otel_test"], 14330);
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec![
+            "spawn_otel_test",
+            "spawned_blocking",
+            "spawned_rayon",
+            "spawned_task",
+            "spawned_thread"
+        ]
+    );
+    for span_name in [
+        "spawned_blocking",
+        "spawned_rayon",
+        "spawned_task",
+        "spawned_thread",
+    ] {
+        assert_eq!(
+            result.get_parent_span_name(span_name),
+            Some("spawn_otel_test".to_string()),
+            "`{}` is not a child of the test span",
+            span_name
+        );
+    }
+}
+
+#[tokio::test]
+async fn check_warning_orphan_span_otel_test() {
+    // given
+    let collector = InProcessCollector::start(14331).await;
+
+    // when
+    let output = tokio::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "tests::spawn_test_case::orphan_span_otel_test",
+            "--exact",
+            "--nocapture",
+        ])
+        .output()
+        .await
+        .expect("Failed to execute test");
+
+    // then
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("span `detached_task`")
+            && stderr.contains("was created without a parent while a test is running"),
+        "{}",
+        stderr
+    );
+    let result = collector.trace_content();
+    assert_eq!(result.get_parent_span_name("detached_task"), None);
+}
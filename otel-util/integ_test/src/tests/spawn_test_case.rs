@@ -0,0 +1,30 @@
+use otel_util::{tracing, use_otel_at_test};
+
+#[tracing::instrument]
+async fn spawned_task() {}
+
+#[tracing::instrument]
+fn spawned_blocking() {}
+
+#[tracing::instrument]
+fn spawned_thread() {}
+
+#[tracing::instrument]
+fn spawned_rayon() {}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14330", flavor = "multi_thread")]
+async fn spawn_otel_test() {
+    otel_util::spawn(spawned_task()).await.unwrap();
+    otel_util::spawn_blocking(spawned_blocking).await.unwrap();
+    otel_util::thread::spawn(spawned_thread).join().unwrap();
+    otel_util::rayon::scope(|scope| scope.spawn(|_| spawned_rayon()));
+}
+
+#[tracing::instrument]
+async fn detached_task() {}
+
+// tokio::spawn では span が引き継がれないため、detached_task は別の trace になり、警告が出力される。
+#[use_otel_at_test(endpoint = "grpc://localhost:14331")]
+async fn orphan_span_otel_test() {
+    tokio::spawn(detached_task()).await.unwrap();
+}
@@ -0,0 +1,92 @@
+use super::integ_test_executor::InProcessTestExecutor;
+
+#[tokio::test]
+async fn check_otlp_output_step_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &[
+            "tests::step_test_case::passed_steps_otel_test",
+            "tests::step_test_case::failed_async_step_otel_test",
+            "tests::step_test_case::failed_without_panic_steps_otel_test",
+            "tests::step_test_case::sync_steps::failed_sync_step_otel_test",
+        ],
+        14344,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    // step は test の root span の子 span になり、step の中の span はその子 span になる。
+    assert_eq!(
+        result.get_parent_span_name("given").as_deref(),
+        Some("passed_steps_otel_test")
+    );
+    assert_eq!(
+        result.get_parent_span_name("prepare").as_deref(),
+        Some("given")
+    );
+    assert_eq!(
+        result.get_parent_span_name("sample_add").as_deref(),
+        Some("when")
+    );
+    assert_eq!(
+        result.get_parent_span_name("then").as_deref(),
+        Some("passed_steps_otel_test")
+    );
+    assert_eq!(result.get_span_attributes("given")["test.step"], "given");
+    assert!(!result
+        .get_span_attributes("passed_steps_otel_test")
+        .contains_key("test.failed_step"));
+
+    // 失敗した step は、root span の test.failed_step と step の span の test.step.failed に記録される。
+    assert_eq!(
+        result.get_span_attributes("failed_async_step_otel_test")["test.failed_step"],
+        "act"
+    );
+    assert!(result
+        .get_span_attributes("act")
+        .contains_key("test.step.failed"));
+    assert!(!result
+        .get_span_attributes("arrange")
+        .contains_key("test.step.failed"));
+    assert!(!result.get_span_names().contains(&"assert".to_string()));
+
+    assert_eq!(
+        result.get_span_attributes("failed_sync_step_otel_test")["test.failed_step"],
+        "sync_act"
+    );
+    assert!(result
+        .get_span_attributes("sync_act")
+        .contains_key("test.step.failed"));
+    assert!(!result
+        .get_span_attributes("sync_arrange")
+        .contains_key("test.step.failed"));
+
+    for step_name in [
+        "log_error",
+        "soft_assert",
+        "sync_return_err",
+        "async_return_err",
+    ] {
+        assert!(
+            result
+                .get_span_attributes(step_name)
+                .contains_key("test.step.failed"),
+            "{}",
+            step_name
+        );
+    }
+    assert!(!result
+        .get_span_attributes("return_ok")
+        .contains_key("test.step.failed"));
+    assert_eq!(
+        result.get_span_event_names()["async_return_err"],
+        vec!["step returned an error"]
+    );
+    // test.failed_step には、最後に失敗した step が記録される。
+    assert_eq!(
+        result.get_span_attributes("failed_without_panic_steps_otel_test")["test.failed_step"],
+        "async_return_err"
+    );
+}
@@ -0,0 +1,53 @@
+use otel_util::{given, otel_assert_eq, step, then, tracing, use_otel_at_test, when};
+
+#[tracing::instrument]
+async fn sample_add(a: u64, b: u64) -> u64 {
+    a + b
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14344")]
+async fn passed_steps_otel_test() {
+    let (a, b) = given!({ tracing::info_span!("prepare").in_scope(|| (10, 20)) });
+    let c = when!(async { sample_add(a, b).await }).await;
+    then!({
+        assert_eq!(c, 30);
+    });
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14344")]
+#[should_panic]
+async fn failed_async_step_otel_test() {
+    let a = step!("arrange", { 10 });
+    let c = step!("act", async move {
+        let c = sample_add(a, 20).await;
+        assert_eq!(c, 31);
+        c
+    })
+    .await;
+    step!("assert", { assert_eq!(c, 31) });
+}
+
+// panic しなくても、ERROR の event、soft mode の assert の失敗、err を指定した step の Err の出力は step の失敗として記録する。
+#[use_otel_at_test(endpoint = "grpc://localhost:14344")]
+#[should_panic(expected = "1 soft assertion(s) failed")]
+async fn failed_without_panic_steps_otel_test() {
+    step!("log_error", { tracing::error!("something went wrong") });
+    step!("soft_assert", { otel_assert_eq!(soft; 1 + 1, 3) });
+    let _: Result<(), &str> = step!("sync_return_err", err, { Err("failed") });
+    let _: Result<(), &str> = step!("async_return_err", err, async { Err("failed") }).await;
+    step!("return_ok", err, { Ok::<(), &str>(()) }).unwrap();
+}
+
+#[otel_util::otel_tests(endpoint = "grpc://localhost:14344", sync_tests = true)]
+mod sync_steps {
+    use otel_util::step;
+
+    #[test]
+    #[should_panic]
+    fn failed_sync_step_otel_test() {
+        step!("sync_arrange", {});
+        step!("sync_act", {
+            panic!("failed in sync step");
+        });
+    }
+}
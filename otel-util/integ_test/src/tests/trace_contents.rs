@@ -1,3 +1,4 @@
+use opentelemetry_proto::tonic::common::v1::any_value::Value::StringValue;
 use opentelemetry_proto::tonic::trace::v1::ResourceSpans;
 use std::vec;
 
@@ -16,6 +17,7 @@ impl TraceInfoExtractor for ResourceSpans {
             .map_or("".to_string(), |attr| {
                 if let Some(service_value) = &attr.value {
                     match service_value.value {
+                        Some(StringValue(ref v)) => v.clone(),
                         Some(ref v) => {
                             format!("{:?}", v)
                         }
@@ -100,6 +102,38 @@ impl TraceContent {
         ans
     }
 
+    pub fn get_service_names(&self) -> Vec<String> {
+        let mut ans: Vec<String> = self
+            .trace
+            .iter()
+            .map(|resource_span| resource_span.get_service_name())
+            .collect();
+        ans.sort();
+        ans.dedup();
+        ans
+    }
+
+    // resource の attribute を、文字列に変換して取得する。
+    pub fn get_resource_attributes(&self) -> std::collections::HashMap<String, String> {
+        let mut attributes = std::collections::HashMap::new();
+        for resource_span in &self.trace {
+            for attr in resource_span
+                .resource
+                .iter()
+                .flat_map(|resource| &resource.attributes)
+            {
+                if let Some(value) = attr.value.as_ref().and_then(|v| v.value.as_ref()) {
+                    let value = match value {
+                        StringValue(s) => s.clone(),
+                        other => format!("{:?}", other),
+                    };
+                    attributes.insert(attr.key.clone(), value);
+                }
+            }
+        }
+        attributes
+    }
+
     pub fn span_count(&self) -> usize {
         self.trace
             .iter()
@@ -153,4 +187,117 @@ impl TraceContent {
         }
         span_event_names
     }
+
+    // 指定した名前の span の attribute を、文字列に変換して取得する。
+    pub fn get_span_attributes(
+        &self,
+        span_name: &str,
+    ) -> std::collections::HashMap<String, String> {
+        let mut attributes = std::collections::HashMap::new();
+        for resource_span in &self.trace {
+            for scope_span in &resource_span.scope_spans {
+                for span in scope_span
+                    .spans
+                    .iter()
+                    .filter(|span| span.name == span_name)
+                {
+                    for attr in &span.attributes {
+                        if let Some(value) = attr.value.as_ref().and_then(|v| v.value.as_ref()) {
+                            let value = match value {
+                                opentelemetry_proto::tonic::common::v1::any_value::Value::StringValue(s) => s.clone(),
+                                other => format!("{:?}", other),
+                            };
+                            attributes.insert(attr.key.clone(), value);
+                        }
+                    }
+                }
+            }
+        }
+        attributes
+    }
+
+    // 指定した名前の span の親 span の名前を取得する。root span の場合は None になる。
+    pub fn get_parent_span_name(&self, span_name: &str) -> Option<String> {
+        let spans: Vec<_> = self
+            .trace
+            .iter()
+            .flat_map(|resource_span| &resource_span.scope_spans)
+            .flat_map(|scope_span| &scope_span.spans)
+            .collect();
+        let span = spans.iter().find(|span| span.name == span_name)?;
+        spans
+            .iter()
+            .find(|parent| parent.span_id == span.parent_span_id)
+            .map(|parent| parent.name.clone())
+    }
+
+    // 指定した名前の span に記録された、指定した名前の event の attribute を、文字列に変換して取得する。
+    pub fn get_span_event_attributes(
+        &self,
+        span_name: &str,
+        event_name: &str,
+    ) -> Vec<std::collections::HashMap<String, String>> {
+        self.trace
+            .iter()
+            .flat_map(|resource_span| &resource_span.scope_spans)
+            .flat_map(|scope_span| &scope_span.spans)
+            .filter(|span| span.name == span_name)
+            .flat_map(|span| &span.events)
+            .filter(|event| event.name == event_name)
+            .map(|event| {
+                event
+                    .attributes
+                    .iter()
+                    .filter_map(|attr| {
+                        let value = match attr.value.as_ref()?.value.as_ref()? {
+                            StringValue(s) => s.clone(),
+                            opentelemetry_proto::tonic::common::v1::any_value::Value::IntValue(
+                                i,
+                            ) => i.to_string(),
+                            opentelemetry_proto::tonic::common::v1::any_value::Value::BoolValue(
+                                b,
+                            ) => b.to_string(),
+                            other => format!("{:?}", other),
+                        };
+                        Some((attr.key.clone(), value))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // 指定した名前の span の trace ID を、16 進数の文字列で取得する。
+    pub fn get_trace_id(&self, span_name: &str) -> Option<String> {
+        self.trace
+            .iter()
+            .flat_map(|resource_span| &resource_span.scope_spans)
+            .flat_map(|scope_span| &scope_span.spans)
+            .find(|span| span.name == span_name)
+            .map(|span| span.trace_id.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    // 指定した名前の span が link している span の名前を取得する。
+    pub fn get_linked_span_names(&self, span_name: &str) -> Vec<String> {
+        let spans: Vec<_> = self
+            .trace
+            .iter()
+            .flat_map(|resource_span| &resource_span.scope_spans)
+            .flat_map(|scope_span| &scope_span.spans)
+            .collect();
+        let Some(span) = spans.iter().find(|span| span.name == span_name) else {
+            return vec![];
+        };
+        let mut ans: Vec<String> = span
+            .links
+            .iter()
+            .filter_map(|link| {
+                spans
+                    .iter()
+                    .find(|linked| linked.span_id == link.span_id)
+                    .map(|linked| linked.name.clone())
+            })
+            .collect();
+        ans.sort();
+        ans
+    }
 }
@@ -0,0 +1,68 @@
+use super::in_process_collector::InProcessCollector;
+
+async fn run_test_case(name: &str) -> String {
+    let output = tokio::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            &format!("tests::trace_link_test_case::{}", name),
+            "--exact",
+            "--nocapture",
+        ])
+        .output()
+        .await
+        .expect("Failed to execute test");
+    // panic のメッセージは panic hook から標準出力に、trace の URL は標準エラーに出力される。
+    format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    )
+}
+
+#[tokio::test]
+async fn check_trace_link_otel_test() {
+    // given
+    let collector = InProcessCollector::start(14342).await;
+
+    // when
+    let failed = run_test_case("failed_trace_link_otel_test").await;
+    let passed = run_test_case("passed_trace_link_otel_test").await;
+    let always = run_test_case("always_trace_link_otel_test").await;
+    let sync = run_test_case("sync_trace_link::sync_trace_link_otel_test").await;
+
+    // then
+    let result = collector.trace_content();
+    let trace_id = result.get_trace_id("failed_trace_link_otel_test").unwrap();
+    assert!(
+        failed.contains(&format!(
+            "otel-util: trace {} http://localhost:16686/trace/{}",
+            trace_id, trace_id
+        )),
+        "{}",
+        failed
+    );
+    // current_trace_id() は root span の trace ID を返す。
+    assert!(
+        failed.contains(&format!("see trace {}", trace_id)),
+        "{}",
+        failed
+    );
+
+    assert!(!passed.contains("otel-util: trace"), "{}", passed);
+
+    let trace_id = result.get_trace_id("always_trace_link_otel_test").unwrap();
+    assert!(
+        always.contains(&format!(
+            "otel-util: trace {} https://jaeger.example.com/trace/{}?uiFind=test",
+            trace_id, trace_id
+        )),
+        "{}",
+        always
+    );
+
+    let trace_id = result.get_trace_id("sync_trace_link_otel_test").unwrap();
+    assert!(
+        sync.contains(&format!("otel-util: trace {}", trace_id)),
+        "{}",
+        sync
+    );
+}
@@ -0,0 +1,30 @@
+use otel_util::{otel_tests, use_otel_at_test};
+
+// 失敗したテストでは、既定で trace ID と Jaeger の URL が出力される。
+#[use_otel_at_test(endpoint = "grpc://localhost:14342")]
+#[should_panic]
+async fn failed_trace_link_otel_test() {
+    let trace_id = otel_util::current_trace_id().unwrap();
+    assert_eq!(1 + 1, 3, "see trace {}", trace_id);
+}
+
+// 成功したテストでは、既定では出力されない。
+#[use_otel_at_test(endpoint = "grpc://localhost:14342")]
+async fn passed_trace_link_otel_test() {}
+
+#[use_otel_at_test(
+    endpoint = "grpc://localhost:14342",
+    print_trace = "always",
+    trace_url = "https://jaeger.example.com/trace/{trace_id}?uiFind=test"
+)]
+async fn always_trace_link_otel_test() {}
+
+#[otel_tests(
+    endpoint = "grpc://localhost:14342",
+    sync_tests = true,
+    print_trace = "always"
+)]
+mod sync_trace_link {
+    #[test]
+    fn sync_trace_link_otel_test() {}
+}
@@ -0,0 +1,65 @@
+use super::integ_test_executor::InProcessTestExecutor;
+use otel_util::{PropagationContext, Traced};
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn check_otlp_output_traced_message_otel_test() {
+    // given
+    let executor = InProcessTestExecutor::new(
+        &["tests::traced_message_test_case::traced_message_otel_test"],
+        14329,
+    );
+
+    // when
+    let result = executor.execute().await;
+
+    // then
+    assert_eq!(
+        result.get_span_names(),
+        vec![
+            "audit_order",
+            "process_order",
+            "publish_order",
+            "publish_order",
+            "traced_message_otel_test"
+        ]
+    );
+    // 子の span は送信側の span の子になり、link の span は新しい trace の root になる。
+    assert_eq!(
+        result.get_parent_span_name("process_order"),
+        Some("publish_order".to_string())
+    );
+    assert_eq!(result.get_parent_span_name("audit_order"), None);
+    assert_eq!(
+        result.get_linked_span_names("audit_order"),
+        vec!["publish_order"]
+    );
+}
+
+#[test]
+fn traced_serializes_context_with_payload() {
+    // given
+    let traced = Traced {
+        context: PropagationContext(HashMap::from([(
+            "traceparent".to_string(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string(),
+        )])),
+        payload: vec![1, 2, 3],
+    };
+
+    // when
+    let json = serde_json::to_value(&traced).unwrap();
+    let deserialized: Traced<Vec<u8>> = serde_json::from_value(json.clone()).unwrap();
+
+    // then
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "context": {
+                "traceparent": "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+            },
+            "payload": [1, 2, 3]
+        })
+    );
+    assert_eq!(deserialized.into_inner(), vec![1, 2, 3]);
+}
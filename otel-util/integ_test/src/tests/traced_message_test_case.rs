@@ -0,0 +1,40 @@
+use otel_util::serde::{Deserialize, Serialize};
+use otel_util::{tracing, use_otel_at_test, Traced};
+use tokio::sync::mpsc;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "otel_util::serde")]
+struct Order {
+    id: u64,
+}
+
+#[tracing::instrument(skip(sender))]
+async fn publish_order(sender: &mpsc::Sender<String>, id: u64) {
+    let message = serde_json::to_string(&Traced::new(Order { id })).unwrap();
+    sender.send(message).await.unwrap();
+}
+
+// メッセージの受信側。送信側とは別の task で動くため、span は Traced から作る。
+async fn consume_orders(mut receiver: mpsc::Receiver<String>) {
+    let message: Traced<Order> = serde_json::from_str(&receiver.recv().await.unwrap()).unwrap();
+    let (order, span) = message.into_inner_with_span("process_order");
+    span.in_scope(|| tracing::info!(order.id, "order processed"));
+
+    let message: Traced<Order> = serde_json::from_str(&receiver.recv().await.unwrap()).unwrap();
+    let (order, span) = message.into_inner_with_linked_span("audit_order");
+    span.in_scope(|| tracing::info!(order.id, "order audited"));
+}
+
+#[use_otel_at_test(endpoint = "grpc://localhost:14329")]
+async fn traced_message_otel_test() {
+    // given
+    let (sender, receiver) = mpsc::channel(2);
+    let consumer = tokio::spawn(consume_orders(receiver));
+
+    // when
+    publish_order(&sender, 1).await;
+    publish_order(&sender, 2).await;
+
+    // then
+    consumer.await.unwrap();
+}
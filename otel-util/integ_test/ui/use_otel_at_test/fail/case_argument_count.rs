@@ -0,0 +1,8 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test]
+#[case(10, 20, 30)]
+#[case(1, 2)]
+async fn case_argument_count(a: u64, b: u64, expected: u64) {}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test]
+#[case(10, 20)]
+async fn case_without_params() {}
+
+fn main() {}
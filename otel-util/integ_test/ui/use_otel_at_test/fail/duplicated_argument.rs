@@ -0,0 +1,6 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(endpoint = "grpc://localhost:4317", endpoint = "grpc://localhost:4318")]
+async fn duplicated_argument() {}
+
+fn main() {}
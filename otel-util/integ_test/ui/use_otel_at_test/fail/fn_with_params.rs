@@ -0,0 +1,6 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test]
+async fn fn_with_params(a: u64, b: u64) {}
+
+fn main() {}
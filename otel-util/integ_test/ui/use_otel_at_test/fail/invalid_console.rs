@@ -0,0 +1,6 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(console = "pretty")]
+async fn unknown_console() {}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(export = "never")]
+async fn invalid_export() {}
+
+#[use_otel_at_test(export = "on_failure", slow_threshold_ms = "1s")]
+async fn non_integer_slow_threshold() {}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(level = "my_crate=verbose")]
+async fn unknown_level() {}
+
+#[use_otel_at_test(level = "my_crate[request{}]=debug")]
+async fn empty_span_field() {}
+
+#[use_otel_at_test(level = 2)]
+async fn non_string_level() {}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(log_bridge = "warn,hyper=loud")]
+async fn unknown_level() {}
+
+#[use_otel_at_test(log_bridge = "=debug")]
+async fn empty_target() {}
+
+#[use_otel_at_test(log_bridge = 1)]
+async fn non_string_log_bridge() {}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(headers = "authorization")]
+async fn header_without_value() {}
+
+#[use_otel_at_test(compression = "zstd")]
+async fn unknown_compression() {}
+
+#[use_otel_at_test(tls_ca = 1)]
+async fn non_string_tls_ca() {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(flavor = "multi_thread", worker_threads = "2")]
+async fn invalid_tokio_argument() {}
+
+fn main() {}
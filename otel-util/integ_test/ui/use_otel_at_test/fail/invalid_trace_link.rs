@@ -0,0 +1,9 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(print_trace = "sometimes")]
+async fn unknown_print_trace() {}
+
+#[use_otel_at_test(trace_url = "http://localhost:16686/search")]
+async fn trace_url_without_placeholder() {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test]
+fn non_async_fn() {}
+
+fn main() {}
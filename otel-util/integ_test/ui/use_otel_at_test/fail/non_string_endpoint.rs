@@ -0,0 +1,6 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(endpoint = 4317)]
+async fn non_string_endpoint() {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(multi_thread)]
+async fn not_key_value() {}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use otel_util::otel_tests;
+
+#[otel_tests(sync_tests = true)]
+mod tests {
+    #[test]
+    async fn async_fn_with_test_attribute() {}
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(endpont = "grpc://localhost:4317")]
+async fn unknown_argument() {}
+
+fn main() {}
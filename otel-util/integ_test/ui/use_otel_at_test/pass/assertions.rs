@@ -0,0 +1,16 @@
+use otel_util::{otel_assert, otel_assert_eq, otel_assert_ne, use_otel_at_test};
+
+#[use_otel_at_test]
+async fn assertions() {
+    let value = 1;
+    otel_assert!(value == 1);
+    otel_assert!(value == 1, "value is {}", value);
+    otel_assert_eq!(value, 1);
+    otel_assert_eq!(value, 1, "value is {}", value);
+    otel_assert_ne!(value, 2,);
+    otel_assert!(soft; value == 1);
+    otel_assert_eq!(soft; value, 1, "value is {}", value);
+    otel_assert_ne!(soft; value, 2);
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test]
+#[case(10, 20, 30)]
+#[case(1, 2, 3)]
+async fn add(a: u64, b: u64, expected: u64) {
+    assert_eq!(expected, a + b);
+}
+
+#[use_otel_at_test(flavor = "current_thread")]
+#[case("a".to_string())]
+async fn returns_result(name: String) -> Result<(), String> {
+    if name.is_empty() {
+        return Err(name);
+    }
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(console = "json")]
+async fn json_console() {}
+
+#[use_otel_at_test(console = "tree")]
+async fn tree_console() {}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use otel_util::{otel_tests, use_otel_at_test};
+
+#[use_otel_at_test(export = "on_failure")]
+async fn on_failure() {}
+
+#[use_otel_at_test(export = "on_failure", slow_threshold_ms = 500)]
+async fn on_failure_or_slow() {}
+
+#[use_otel_at_test(export = "always")]
+async fn always() {}
+
+#[otel_tests(export = "on_failure")]
+mod module_on_failure {
+    #[tokio::test]
+    async fn on_failure() {}
+}
+
+fn main() {}
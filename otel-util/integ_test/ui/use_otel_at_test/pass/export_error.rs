@@ -0,0 +1,9 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(export_error = "fail")]
+async fn fail_on_export_error() {}
+
+#[use_otel_at_test(export_error = "ignore", export = "on_failure")]
+async fn ignore_export_error() {}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(probe_timeout_ms = 200, fallback = "file:/tmp/spans.jsonl")]
+async fn fallback_to_file() {}
+
+#[use_otel_at_test(probe_timeout_ms = 0, fallback = "noop")]
+async fn without_probe() {}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(level = "debug")]
+async fn debug_level() {}
+
+#[use_otel_at_test(level = "info,my_crate=trace,hyper=off")]
+async fn target_level() {}
+
+#[use_otel_at_test(level = "info,my_crate[request{user=alice}]=debug")]
+async fn span_field_level() {}
+
+#[use_otel_at_test(level = "[request]=debug,my_crate[request{path=/api/.*}]=trace")]
+async fn span_regex_level() {}
+
+#[use_otel_at_test(level = "my_crate")]
+async fn target_only_level() {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(log_bridge = "warn,hyper=error,my_crate::db=DEBUG")]
+async fn log_bridge() {}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(
+    endpoint = "https://collector.example.com:4317",
+    tls_ca = "certs/ca.pem",
+    headers = "authorization=Bearer ${OTEL_TOKEN},x-tenant=ci",
+    compression = "gzip",
+    export_timeout_ms = 3000
+)]
+async fn tls_with_headers() {}
+
+#[use_otel_at_test(
+    tls_ca = "certs/ca.pem",
+    tls_client_cert = "certs/client.pem",
+    tls_client_key = "certs/client.key",
+    compression = "none"
+)]
+async fn mutual_tls() {}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use otel_util::{otel_tests, use_otel_at_test};
+
+#[use_otel_at_test(propagators = "tracecontext,baggage,b3,b3multi,jaeger")]
+async fn all_propagators() {}
+
+#[use_otel_at_test(propagators = "none", flavor = "multi_thread")]
+async fn no_propagators() {}
+
+#[otel_tests(propagators = "b3")]
+mod module_with_propagators {
+    #[tokio::test]
+    async fn b3_only() {}
+}
+
+fn main() {}
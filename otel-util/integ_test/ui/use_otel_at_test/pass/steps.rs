@@ -0,0 +1,14 @@
+use otel_util::{given, step, then, use_otel_at_test, when};
+
+#[use_otel_at_test]
+async fn steps() {
+    let value = given!({ 1 });
+    let value = when!(async move { value + 1 }).await;
+    let value = step!("double", async { value * 2 }).await;
+    then!({
+        assert_eq!(value, 4);
+    });
+    step!("empty", {});
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(endpoint = "grpc://localhost:4317")]
+async fn endpoint_only() {}
+
+#[use_otel_at_test(flavor = "multi_thread", worker_threads = 2)]
+async fn multi_thread() {}
+
+#[use_otel_at_test(flavor = "current_thread", crate = "tokio")]
+async fn current_thread() -> Result<(), String> {
+    Ok(())
+}
+
+#[use_otel_at_test(start_paused = true)]
+async fn start_paused() {}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use otel_util::use_otel_at_test;
+
+#[use_otel_at_test(print_trace = "always")]
+async fn always_print_trace() {
+    let _trace_id: Option<String> = otel_util::current_trace_id();
+}
+
+#[use_otel_at_test(
+    print_trace = "on_failure",
+    trace_url = "https://jaeger.example.com/trace/{trace_id}"
+)]
+async fn custom_trace_url() {}
+
+#[use_otel_at_test(print_trace = "never")]
+async fn never_print_trace() {}
+
+fn main() {}
@@ -0,0 +1,425 @@
+use options::{parse_fallback_name, propagator_indexes, FallbackName};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Error, Lit, LitStr};
+
+// guard factory の builder に渡す設定の引数。use_otel_at_test と otel_tests で共通。
+pub(crate) const GUARD_OPTION_KEYS: [&str; 17] = [
+    "propagators",
+    "export",
+    "slow_threshold_ms",
+    "export_error",
+    "probe_timeout_ms",
+    "fallback",
+    "tls_ca",
+    "tls_client_cert",
+    "tls_client_key",
+    "headers",
+    "compression",
+    "export_timeout_ms",
+    "print_trace",
+    "trace_url",
+    "log_bridge",
+    "level",
+    "console",
+];
+
+const LOG_LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+#[derive(Default)]
+pub(crate) struct GuardOptions {
+    builder_calls: Vec<TokenStream2>,
+}
+
+impl GuardOptions {
+    pub(crate) fn is_key(key: &str) -> bool {
+        GUARD_OPTION_KEYS.contains(&key)
+    }
+
+    pub(crate) fn parse(&mut self, key: &str, lit: &Lit) -> syn::Result<()> {
+        match key {
+            "propagators" => self.parse_propagators(lit),
+            "export" => self.parse_export(lit),
+            "slow_threshold_ms" => self.parse_slow_threshold_ms(lit),
+            "export_error" => self.parse_export_error(lit),
+            "probe_timeout_ms" => self.parse_probe_timeout_ms(lit),
+            "fallback" => self.parse_fallback(lit),
+            "tls_ca" => self.parse_path(key, lit, quote! { with_tls_ca_certificate }),
+            "tls_client_cert" => self.parse_path(key, lit, quote! { with_tls_client_certificate }),
+            "tls_client_key" => self.parse_path(key, lit, quote! { with_tls_client_key }),
+            "headers" => self.parse_headers(lit),
+            "compression" => self.parse_compression(lit),
+            "export_timeout_ms" => self.parse_export_timeout_ms(lit),
+            "print_trace" => self.parse_print_trace(lit),
+            "trace_url" => self.parse_trace_url(lit),
+            "log_bridge" => self.parse_log_bridge(lit),
+            "level" => self.parse_level(lit),
+            "console" => self.parse_console(lit),
+            _ => unreachable!("`{}` is not a guard option", key),
+        }
+    }
+
+    // propagators = "tracecontext,baggage,b3" のように、カンマ区切りで指定する。
+    fn parse_propagators(&mut self, lit: &Lit) -> syn::Result<()> {
+        let Lit::Str(s) = lit else {
+            return Err(Error::new_spanned(
+                lit,
+                "`propagators` must be a string literal, e.g. `propagators = \"tracecontext,baggage\"`",
+            ));
+        };
+        propagator_indexes(&s.value()).map_err(|e| Error::new_spanned(s, e))?;
+        self.builder_calls.push(quote! {
+            .with_propagators(&otel_util::PropagatorKind::parse_list(#s).unwrap())
+        });
+        Ok(())
+    }
+
+    // export = "on_failure" の場合、失敗したテストの trace だけを送信する。
+    fn parse_export(&mut self, lit: &Lit) -> syn::Result<()> {
+        let mode = match lit {
+            Lit::Str(s) if s.value() == "always" => quote! { Always },
+            Lit::Str(s) if s.value() == "on_failure" => quote! { OnFailure },
+            lit => {
+                return Err(Error::new_spanned(
+                    lit,
+                    "`export` must be `\"always\"` or `\"on_failure\"`",
+                ))
+            }
+        };
+        self.builder_calls
+            .push(quote! { .with_export_mode(otel_util::ExportMode::#mode) });
+        Ok(())
+    }
+
+    // export = "on_failure" で、成功したテストでもこの時間 (ミリ秒) を超えたら送信する。
+    fn parse_slow_threshold_ms(&mut self, lit: &Lit) -> syn::Result<()> {
+        let Lit::Int(ms) = lit else {
+            return Err(Error::new_spanned(
+                lit,
+                "`slow_threshold_ms` must be an integer literal, e.g. `slow_threshold_ms = 1000`",
+            ));
+        };
+        let ms: u64 = ms.base10_parse()?;
+        self.builder_calls
+            .push(quote! { .with_slow_threshold(std::time::Duration::from_millis(#ms)) });
+        Ok(())
+    }
+
+    // export_error = "fail" の場合、trace の送信に失敗したらテストを失敗させる。
+    fn parse_export_error(&mut self, lit: &Lit) -> syn::Result<()> {
+        let policy = match lit {
+            Lit::Str(s) if s.value() == "ignore" => quote! { Ignore },
+            Lit::Str(s) if s.value() == "warn" => quote! { Warn },
+            Lit::Str(s) if s.value() == "fail" => quote! { Fail },
+            lit => {
+                return Err(Error::new_spanned(
+                    lit,
+                    "`export_error` must be `\"ignore\"`, `\"warn\"` or `\"fail\"`",
+                ))
+            }
+        };
+        self.builder_calls
+            .push(quote! { .with_export_error_policy(otel_util::ExportErrorPolicy::#policy) });
+        Ok(())
+    }
+
+    // 初回の初期化時に collector へ接続を確認する timeout (ミリ秒)。0 の場合は確認しない。
+    fn parse_probe_timeout_ms(&mut self, lit: &Lit) -> syn::Result<()> {
+        let Lit::Int(ms) = lit else {
+            return Err(Error::new_spanned(
+                lit,
+                "`probe_timeout_ms` must be an integer literal, e.g. `probe_timeout_ms = 200`",
+            ));
+        };
+        let ms: u64 = ms.base10_parse()?;
+        self.builder_calls.push(if ms == 0 {
+            quote! { .without_connectivity_probe() }
+        } else {
+            quote! { .with_probe_timeout(std::time::Duration::from_millis(#ms)) }
+        });
+        Ok(())
+    }
+
+    // collector に接続できなかった場合の送り先。"noop", "stdout", "file:<path>" のいずれか。
+    fn parse_fallback(&mut self, lit: &Lit) -> syn::Result<()> {
+        let error = || {
+            Error::new_spanned(
+                lit,
+                "`fallback` must be `\"noop\"`, `\"stdout\"` or `\"file:<path>\"`",
+            )
+        };
+        let Lit::Str(s) = lit else {
+            return Err(error());
+        };
+        let value = s.value();
+        let sink = match parse_fallback_name(&value).map_err(|e| Error::new_spanned(s, e))? {
+            FallbackName::NoOp => quote! { otel_util::FallbackSink::NoOp },
+            FallbackName::Stdout => quote! { otel_util::FallbackSink::Stdout },
+            FallbackName::File(path) => {
+                quote! { otel_util::FallbackSink::File(std::path::PathBuf::from(#path)) }
+            }
+        };
+        self.builder_calls
+            .push(quote! { .with_fallback_sink(#sink) });
+        Ok(())
+    }
+
+    // tls_ca, tls_client_cert, tls_client_key は PEM ファイルのパスを指定する。
+    fn parse_path(&mut self, key: &str, lit: &Lit, method: TokenStream2) -> syn::Result<()> {
+        let Lit::Str(path) = lit else {
+            return Err(Error::new_spanned(
+                lit,
+                format!(
+                    "`{}` must be a string literal, e.g. `{} = \"certs/ca.pem\"`",
+                    key, key
+                ),
+            ));
+        };
+        self.builder_calls.push(quote! { .#method(#path) });
+        Ok(())
+    }
+
+    // headers = "authorization=Bearer ${TOKEN},x-tenant=ci" のように、カンマ区切りで指定する。
+    // ${TOKEN} は実行時に環境変数の値に置き換えられる。
+    fn parse_headers(&mut self, lit: &Lit) -> syn::Result<()> {
+        let Lit::Str(s) = lit else {
+            return Err(Error::new_spanned(
+                lit,
+                "`headers` must be a string literal, e.g. `headers = \"authorization=Bearer ${TOKEN}\"`",
+            ));
+        };
+        for header in s.value().split(',').filter(|h| !h.trim().is_empty()) {
+            let Some((name, value)) = header
+                .split_once('=')
+                .filter(|(name, _)| !name.trim().is_empty())
+            else {
+                return Err(Error::new_spanned(
+                    s,
+                    format!("invalid header `{}`; expected `name=value`", header),
+                ));
+            };
+            let (name, value) = (name.trim(), value.trim());
+            self.builder_calls
+                .push(quote! { .with_header(#name, #value) });
+        }
+        Ok(())
+    }
+
+    fn parse_compression(&mut self, lit: &Lit) -> syn::Result<()> {
+        let compression = match lit {
+            Lit::Str(s) if s.value() == "none" => quote! { None },
+            Lit::Str(s) if s.value() == "gzip" => quote! { Gzip },
+            lit => {
+                return Err(Error::new_spanned(
+                    lit,
+                    "`compression` must be `\"none\"` or `\"gzip\"`",
+                ))
+            }
+        };
+        self.builder_calls
+            .push(quote! { .with_compression(otel_util::OtlpCompression::#compression) });
+        Ok(())
+    }
+
+    // 1 回の送信の timeout (ミリ秒)。
+    fn parse_export_timeout_ms(&mut self, lit: &Lit) -> syn::Result<()> {
+        let Lit::Int(ms) = lit else {
+            return Err(Error::new_spanned(
+                lit,
+                "`export_timeout_ms` must be an integer literal, e.g. `export_timeout_ms = 3000`",
+            ));
+        };
+        let ms: u64 = ms.base10_parse()?;
+        self.builder_calls
+            .push(quote! { .with_export_timeout(std::time::Duration::from_millis(#ms)) });
+        Ok(())
+    }
+
+    // テストの終了時に trace ID と URL を出力するか。"never", "on_failure", "always" のいずれか。
+    fn parse_print_trace(&mut self, lit: &Lit) -> syn::Result<()> {
+        let print_trace = match lit {
+            Lit::Str(s) if s.value() == "never" => quote! { Never },
+            Lit::Str(s) if s.value() == "on_failure" => quote! { OnFailure },
+            Lit::Str(s) if s.value() == "always" => quote! { Always },
+            lit => {
+                return Err(Error::new_spanned(
+                    lit,
+                    "`print_trace` must be `\"never\"`, `\"on_failure\"` or `\"always\"`",
+                ))
+            }
+        };
+        self.builder_calls
+            .push(quote! { .with_print_trace(otel_util::PrintTrace::#print_trace) });
+        Ok(())
+    }
+
+    // trace_url = "https://jaeger.example.com/trace/{trace_id}" のように、{trace_id} を含めて指定する。
+    fn parse_trace_url(&mut self, lit: &Lit) -> syn::Result<()> {
+        let Lit::Str(s) = lit else {
+            return Err(Error::new_spanned(
+                lit,
+                "`trace_url` must be a string literal, e.g. `trace_url = \"http://localhost:16686/trace/{trace_id}\"`",
+            ));
+        };
+        if !s.value().contains("{trace_id}") {
+            return Err(Error::new_spanned(
+                s,
+                "`trace_url` must contain `{trace_id}`",
+            ));
+        }
+        self.builder_calls.push(quote! { .with_trace_url(#s) });
+        Ok(())
+    }
+
+    // log_bridge = "warn,hyper=error" のように、log crate の record を記録する level を target ごとに指定する。
+    fn parse_log_bridge(&mut self, lit: &Lit) -> syn::Result<()> {
+        let Lit::Str(s) = lit else {
+            return Err(Error::new_spanned(
+                lit,
+                "`log_bridge` must be a string literal, e.g. `log_bridge = \"warn,hyper=error\"`",
+            ));
+        };
+        validate_directives(s)?;
+        self.builder_calls.push(quote! { .with_log_bridge(#s) });
+        Ok(())
+    }
+
+    // level = "debug" や level = "info,my_crate=debug" のように、記録する span / event の level を指定する。
+    fn parse_level(&mut self, lit: &Lit) -> syn::Result<()> {
+        let Lit::Str(s) = lit else {
+            return Err(Error::new_spanned(
+                lit,
+                "`level` must be a string literal, e.g. `level = \"debug\"`",
+            ));
+        };
+        validate_env_filter(s)?;
+        self.builder_calls.push(quote! { .with_log_level(#s) });
+        Ok(())
+    }
+
+    // 標準出力への出力形式。"off", "full", "compact", "json", "tree" のいずれか。
+    fn parse_console(&mut self, lit: &Lit) -> syn::Result<()> {
+        let output = match lit {
+            Lit::Str(s) if s.value() == "off" => quote! { Off },
+            Lit::Str(s) if s.value() == "full" => quote! { Full },
+            Lit::Str(s) if s.value() == "compact" => quote! { Compact },
+            Lit::Str(s) if s.value() == "json" => quote! { Json },
+            Lit::Str(s) if s.value() == "tree" => quote! { Tree },
+            lit => return Err(Error::new_spanned(
+                lit,
+                "`console` must be `\"off\"`, `\"full\"`, `\"compact\"`, `\"json\"` or `\"tree\"`",
+            )),
+        };
+        self.builder_calls
+            .push(quote! { .with_console_output(otel_util::ConsoleOutput::#output) });
+        Ok(())
+    }
+
+    // DefaultSimpleOtelGuardFactory::new(...) と build() の間に挟む builder の呼び出し。
+    pub(crate) fn builder_calls(&self) -> TokenStream2 {
+        let builder_calls = &self.builder_calls;
+        quote! { #(#builder_calls)* }
+    }
+}
+
+// "warn,hyper=error" のような、level または target=level のカンマ区切り (log_bridge の形式)。
+fn validate_directives(s: &LitStr) -> syn::Result<()> {
+    for directive in s
+        .value()
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+    {
+        let level = match directive.split_once('=') {
+            Some((target, level)) if !target.trim().is_empty() => level.trim(),
+            Some(_) => {
+                return Err(Error::new_spanned(
+                    s,
+                    format!(
+                        "invalid directive `{}`; expected `level` or `target=level`",
+                        directive
+                    ),
+                ))
+            }
+            None => directive,
+        };
+        if !LOG_LEVELS.contains(&level.to_ascii_lowercase().as_str()) {
+            return Err(Error::new_spanned(
+                s,
+                format!(
+                    "unknown level `{}` in `{}`; expected `off`, `error`, `warn`, `info`, `debug` or `trace`",
+                    level, directive
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// level は実行時に EnvFilter (RUST_LOG と同じ形式) として解釈するため、EnvFilter と同じ文法で検証する。
+// "target[span{field=value}]=level" のような span の field の指定も受け付ける。field の値の正規表現は検証しない。
+fn validate_env_filter(s: &LitStr) -> syn::Result<()> {
+    for directive in s.value().split(',').filter(|d| !d.is_empty()) {
+        if !is_env_filter_directive(directive.trim()) {
+            return Err(Error::new_spanned(
+                s,
+                format!(
+                    "invalid directive `{}`; expected the `RUST_LOG` format, e.g. `debug`, `my_crate=debug` or `my_crate[span{{field=value}}]=debug`",
+                    directive
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// tracing_subscriber::filter::Directive の解析と同じ状態遷移。
+fn is_env_filter_directive(directive: &str) -> bool {
+    enum State {
+        Start,
+        LevelOrTarget,
+        Span,
+        Field { field_start: usize },
+        Fields,
+        Target,
+        Level { level_start: usize },
+    }
+
+    let mut state = State::Start;
+    for (i, c) in directive.char_indices() {
+        state = match (state, c) {
+            (State::Start, '[') => State::Span,
+            (State::Start, c) if !['-', ':', '_'].contains(&c) && !c.is_alphanumeric() => {
+                return false
+            }
+            (State::Start, _) => State::LevelOrTarget,
+            (State::LevelOrTarget, '=') => State::Level { level_start: i + 1 },
+            (State::LevelOrTarget, '[') => State::Span,
+            (state @ State::LevelOrTarget, _) => state,
+            (State::Target, '=') => State::Level { level_start: i + 1 },
+            (State::Span, ']') => State::Target,
+            (State::Span, '{') => State::Field { field_start: i + 1 },
+            (state @ State::Span, _) => state,
+            (State::Field { field_start }, '}') if field_start < i => State::Fields,
+            (state @ State::Field { .. }, c) if c != '}' => state,
+            (State::Fields, ']') => State::Target,
+            (state @ State::Level { .. }, _) => state,
+            _ => return false,
+        };
+    }
+    match state {
+        // level だけ、または target だけ (target のすべての level を記録する)。
+        State::LevelOrTarget | State::Target => true,
+        State::Level { level_start } => {
+            let level = &directive[level_start..];
+            level.is_empty() || is_level(level)
+        }
+        _ => false,
+    }
+}
+
+// LevelFilter の FromStr と同じく、名前 (大文字小文字を区別しない) と 0 から 5 の数字を受け付ける。
+fn is_level(level: &str) -> bool {
+    LOG_LEVELS.contains(&level.to_ascii_lowercase().as_str())
+        || matches!(level.parse::<usize>(), Ok(0..=5))
+}
@@ -0,0 +1,250 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{
+    parse_quote, Attribute, AttributeArgs, Error, FnArg, ImplItem, Item, Lit, Meta, NestedMeta,
+    Pat, PathArguments, ReturnType, Signature, Type,
+};
+
+// instrument_all の設定。
+struct InstrumentAllArgs {
+    level: Option<String>,
+    ret: bool,
+    skip_fns: Vec<String>,
+    skip_args: Vec<String>,
+    skip_arg_types: Vec<String>,
+}
+
+// skip_fns(a, b) のような、識別子のリストを取り出す。
+fn parse_ident_list(list: &syn::MetaList) -> syn::Result<Vec<String>> {
+    list.nested
+        .iter()
+        .map(|nested| match nested {
+            NestedMeta::Meta(Meta::Path(path)) => Ok(path.to_token_stream().to_string()),
+            other => Err(Error::new_spanned(other, "expected an identifier")),
+        })
+        .collect()
+}
+
+impl InstrumentAllArgs {
+    fn new(args: AttributeArgs) -> syn::Result<Self> {
+        let mut instrument_args = InstrumentAllArgs {
+            level: None,
+            ret: false,
+            skip_fns: Vec::new(),
+            skip_args: Vec::new(),
+            skip_arg_types: Vec::new(),
+        };
+        let mut errors: Option<Error> = None;
+        let mut push_error = |error: Error| match errors.as_mut() {
+            Some(errors) => errors.combine(error),
+            None => errors = Some(error),
+        };
+
+        for arg in args {
+            match &arg {
+                NestedMeta::Meta(Meta::NameValue(nv)) => {
+                    let key = nv.path.to_token_stream().to_string();
+                    match (key.as_str(), &nv.lit) {
+                        ("level", Lit::Str(s)) => {
+                            let level = s.value().to_lowercase();
+                            if ["trace", "debug", "info", "warn", "error"].contains(&level.as_str())
+                            {
+                                instrument_args.level = Some(level);
+                            } else {
+                                push_error(Error::new_spanned(
+                                    s,
+                                    "`level` must be one of \"trace\", \"debug\", \"info\", \"warn\", \"error\"",
+                                ));
+                            }
+                        }
+                        ("ret", Lit::Bool(b)) => instrument_args.ret = b.value,
+                        ("level", lit) => push_error(Error::new_spanned(
+                            lit,
+                            "`level` must be a string literal",
+                        )),
+                        ("ret", lit) => push_error(Error::new_spanned(
+                            lit,
+                            "`ret` must be a boolean literal",
+                        )),
+                        _ => push_error(Error::new_spanned(
+                            &nv.path,
+                            format!("unknown argument `{}`", key),
+                        )),
+                    }
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("ret") => {
+                    instrument_args.ret = true;
+                }
+                NestedMeta::Meta(Meta::List(list)) => {
+                    let key = list.path.to_token_stream().to_string();
+                    let target = match key.as_str() {
+                        "skip_fns" => &mut instrument_args.skip_fns,
+                        "skip_args" => &mut instrument_args.skip_args,
+                        "skip_arg_types" => &mut instrument_args.skip_arg_types,
+                        _ => {
+                            push_error(Error::new_spanned(
+                                &list.path,
+                                format!("unknown argument `{}`", key),
+                            ));
+                            continue;
+                        }
+                    };
+                    match parse_ident_list(list) {
+                        Ok(idents) => target.extend(idents),
+                        Err(e) => push_error(e),
+                    }
+                }
+                _ => push_error(Error::new_spanned(
+                    &arg,
+                    "expected one of `level = \"...\"`, `ret`, `skip_fns(...)`, `skip_args(...)`, `skip_arg_types(...)`",
+                )),
+            }
+        }
+
+        match errors {
+            Some(errors) => Err(errors),
+            None => Ok(instrument_args),
+        }
+    }
+}
+
+// 参照を外した型の、最後の path segment 名を返す。 (&mut Connection -> Connection)
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Reference(reference) => type_name(&reference.elem),
+        Type::Paren(paren) => type_name(&paren.elem),
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn returns_result(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(path) => path.path.segments.last().is_some_and(|segment| {
+                segment.ident == "Result"
+                    && matches!(segment.arguments, PathArguments::AngleBracketed(_))
+            }),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
+
+fn returns_unit(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Default => true,
+        ReturnType::Type(_, ty) => {
+            matches!(ty.as_ref(), Type::Tuple(tuple) if tuple.elems.is_empty())
+        }
+    }
+}
+
+// すでに計装されている関数や、テスト関数には付与しない。
+fn should_skip(args: &InstrumentAllArgs, attrs: &[Attribute], sig: &Signature) -> bool {
+    let has_attr = |names: &[&str]| {
+        attrs.iter().any(|attr| {
+            attr.path
+                .segments
+                .last()
+                .is_some_and(|segment| names.iter().any(|name| segment.ident == name))
+        })
+    };
+    sig.constness.is_some()
+        || sig.abi.is_some()
+        || args.skip_fns.contains(&sig.ident.to_string())
+        || has_attr(&["instrument", "test", "use_otel_at_test"])
+}
+
+// #[tracing::instrument(...)] を生成する。self は Debug を実装していないことが多いため常に skip する。
+fn instrument_attr(args: &InstrumentAllArgs, sig: &Signature) -> Attribute {
+    let mut skips = Vec::new();
+    for input in &sig.inputs {
+        match input {
+            FnArg::Receiver(_) => skips.push(quote! { self }),
+            FnArg::Typed(pat_type) => {
+                let Pat::Ident(pat_ident) = pat_type.pat.as_ref() else {
+                    continue;
+                };
+                let name = pat_ident.ident.to_string();
+                let skip_by_type = type_name(&pat_type.ty)
+                    .is_some_and(|type_name| args.skip_arg_types.contains(&type_name));
+                if args.skip_args.contains(&name) || skip_by_type {
+                    let ident = &pat_ident.ident;
+                    skips.push(quote! { #ident });
+                }
+            }
+        }
+    }
+
+    let mut options = Vec::new();
+    if let Some(level) = &args.level {
+        options.push(quote! { level = #level });
+    }
+    if !skips.is_empty() {
+        options.push(quote! { skip(#(#skips),*) });
+    }
+    if returns_result(&sig.output) {
+        options.push(quote! { err });
+    }
+    if args.ret && !returns_unit(&sig.output) {
+        options.push(quote! { ret });
+    }
+    parse_quote! { #[tracing::instrument(#(#options),*)] }
+}
+
+fn instrument_impl_items(args: &InstrumentAllArgs, items: &mut [ImplItem]) {
+    for item in items {
+        if let ImplItem::Method(method) = item {
+            if !should_skip(args, &method.attrs, &method.sig) {
+                let attr = instrument_attr(args, &method.sig);
+                method.attrs.push(attr);
+            }
+        }
+    }
+}
+
+fn instrument_items(args: &InstrumentAllArgs, items: &mut [Item]) {
+    for item in items {
+        match item {
+            Item::Fn(item_fn) if !should_skip(args, &item_fn.attrs, &item_fn.sig) => {
+                let attr = instrument_attr(args, &item_fn.sig);
+                item_fn.attrs.push(attr);
+            }
+            Item::Impl(item_impl) => instrument_impl_items(args, &mut item_impl.items),
+            Item::Mod(item_mod) => {
+                if let Some((_, items)) = item_mod.content.as_mut() {
+                    instrument_items(args, items);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn expand(args: AttributeArgs, mut item: Item) -> syn::Result<TokenStream2> {
+    let args = InstrumentAllArgs::new(args)?;
+    match &mut item {
+        Item::Mod(item_mod) => match item_mod.content.as_mut() {
+            Some((_, items)) => instrument_items(&args, items),
+            None => {
+                return Err(Error::new_spanned(
+                    &item_mod.ident,
+                    "`#[instrument_all]` can only be applied to an inline module, e.g. `mod calc { ... }`",
+                ))
+            }
+        },
+        Item::Impl(item_impl) => instrument_impl_items(&args, &mut item_impl.items),
+        other => {
+            return Err(Error::new_spanned(
+                other,
+                "`#[instrument_all]` can only be applied to a module or an `impl` block",
+            ))
+        }
+    }
+    Ok(item.into_token_stream())
+}
@@ -1,47 +1,464 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, AttributeArgs, ItemFn, Lit, Meta, NestedMeta};
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens};
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_macro_input, Attribute, AttributeArgs, Error, Expr, FnArg, ItemFn, ItemMod, Lit, LitStr,
+    Meta, NestedMeta, Pat, PatType, ReturnType, Token,
+};
+
+mod guard_options;
+mod instrument_all;
+mod otel_tests;
+
+use guard_options::{GuardOptions, GUARD_OPTION_KEYS};
+
+// tokio::test にそのまま渡す引数の許可リスト。
+const TOKIO_TEST_KEYS: [&str; 4] = ["flavor", "worker_threads", "start_paused", "crate"];
 
 struct UseOtelTestArgs {
     pub endpoint: String,
+    pub local: bool,
+    pub guard_options: GuardOptions,
     pub others: Vec<NestedMeta>,
 }
 
 impl UseOtelTestArgs {
-    fn new(args: AttributeArgs) -> Self {
+    fn new(args: AttributeArgs) -> syn::Result<Self> {
         let mut endpoint = "grpc://localhost:4317".to_string();
+        let mut local = false;
+        let mut guard_options = GuardOptions::default();
         let mut other_args = Vec::<NestedMeta>::new();
+        let mut seen = Vec::<String>::new();
+        let mut errors: Option<Error> = None;
+        let mut push_error = |error: Error| match errors.as_mut() {
+            Some(errors) => errors.combine(error),
+            None => errors = Some(error),
+        };
+
         for arg in args {
-            match arg {
-                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("endpoint") => {
-                    if let Lit::Str(s) = &nv.lit {
-                        endpoint = s.value();
+            let nv = match &arg {
+                NestedMeta::Meta(Meta::NameValue(nv)) => nv,
+                _ => {
+                    push_error(Error::new_spanned(
+                        &arg,
+                        "expected `key = value`, e.g. `endpoint = \"grpc://localhost:4317\"`",
+                    ));
+                    continue;
+                }
+            };
+            let key = nv.path.to_token_stream().to_string();
+            if seen.contains(&key) {
+                push_error(Error::new_spanned(
+                    &nv.path,
+                    format!("duplicated argument `{}`", key),
+                ));
+                continue;
+            }
+            seen.push(key.clone());
+
+            match key.as_str() {
+                "endpoint" => match &nv.lit {
+                    Lit::Str(s) => endpoint = s.value(),
+                    lit => push_error(Error::new_spanned(
+                        lit,
+                        "`endpoint` must be a string literal, e.g. `endpoint = \"grpc://localhost:4317\"`",
+                    )),
+                },
+                "local" => match &nv.lit {
+                    Lit::Bool(b) => local = b.value,
+                    lit => push_error(Error::new_spanned(lit, "`local` must be a boolean literal")),
+                },
+                key if GuardOptions::is_key(key) => {
+                    if let Err(e) = guard_options.parse(key, &nv.lit) {
+                        push_error(e);
                     }
                 }
-                _ => {
-                    other_args.push(arg);
+                "flavor" | "crate" => match &nv.lit {
+                    Lit::Str(_) => other_args.push(arg),
+                    lit => push_error(Error::new_spanned(
+                        lit,
+                        format!("`{}` must be a string literal", key),
+                    )),
+                },
+                "worker_threads" => match &nv.lit {
+                    Lit::Int(_) => other_args.push(arg),
+                    lit => push_error(Error::new_spanned(
+                        lit,
+                        "`worker_threads` must be an integer literal",
+                    )),
+                },
+                "start_paused" => match &nv.lit {
+                    Lit::Bool(_) => other_args.push(arg),
+                    lit => push_error(Error::new_spanned(
+                        lit,
+                        "`start_paused` must be a boolean literal",
+                    )),
+                },
+                _ => push_error(Error::new_spanned(
+                    &nv.path,
+                    format!(
+                        "unknown argument `{}`; expected `endpoint`, `local`, {} or one of the tokio::test arguments: {}",
+                        key,
+                        GUARD_OPTION_KEYS
+                            .iter()
+                            .map(|k| format!("`{}`", k))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        TOKIO_TEST_KEYS
+                            .iter()
+                            .map(|k| format!("`{}`", k))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                )),
+            }
+        }
+
+        match errors {
+            Some(errors) => Err(errors),
+            None => Ok(UseOtelTestArgs {
+                endpoint,
+                local,
+                guard_options,
+                others: other_args,
+            }),
+        }
+    }
+}
+
+// #[case(...)] で与えられた、パラメータ化テストの 1 ケース分の引数。
+struct TestCase {
+    attr: Attribute,
+    args: Punctuated<Expr, Token![,]>,
+}
+
+// 関数に付与された #[case(...)] を取り出す。取り出した attribute は展開後のコードには残さない。
+fn take_cases(input: &mut ItemFn) -> syn::Result<Vec<TestCase>> {
+    let mut cases = Vec::new();
+    let mut others = Vec::new();
+    for attr in input.attrs.drain(..) {
+        if attr.path.is_ident("case") {
+            let args = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?;
+            cases.push(TestCase { attr, args });
+        } else {
+            others.push(attr);
+        }
+    }
+    input.attrs = others;
+    Ok(cases)
+}
+
+// tokio::test と同じく、async 関数のみ受け付ける。引数は #[case(...)] で値を与える場合のみ受け付ける。
+fn validate_signature(input: &ItemFn, cases: &[TestCase]) -> syn::Result<()> {
+    let sig = &input.sig;
+    if sig.asyncness.is_none() {
+        return Err(Error::new_spanned(
+            sig.fn_token,
+            "the `async` keyword is missing from the function declaration; `#[use_otel_at_test]` can only be applied to `async fn`",
+        ));
+    }
+    if let Some(receiver) = sig.receiver() {
+        return Err(Error::new_spanned(
+            receiver,
+            "the test function cannot take `self`",
+        ));
+    }
+    if cases.is_empty() && !sig.inputs.is_empty() {
+        return Err(Error::new_spanned(
+            &sig.inputs,
+            "the test function cannot accept arguments unless their values are given with `#[case(...)]`",
+        ));
+    }
+    if sig.inputs.is_empty() {
+        if let Some(case) = cases.first() {
+            return Err(Error::new_spanned(
+                &case.attr,
+                "`#[case(...)]` requires the test function to take the arguments of each case",
+            ));
+        }
+    }
+    for case in cases {
+        if case.args.len() != sig.inputs.len() {
+            return Err(Error::new_spanned(
+                &case.attr,
+                format!(
+                    "expected {} argument(s) in `#[case(...)]`, found {}",
+                    sig.inputs.len(),
+                    case.args.len()
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+struct TestExpansion<'a> {
+    attrs: &'a [Attribute],
+    tokio_test_attrs: &'a TokenStream2,
+    endpoint: &'a str,
+    service_name: &'a str,
+    guard_options: &'a GuardOptions,
+    output: &'a ReturnType,
+    // true の場合は、tokio::spawn の代わりに LocalSet 上で実行する。
+    local: bool,
+}
+
+impl TestExpansion<'_> {
+    // tokio::test として実行され、body 全体を root span で計装するテスト関数を生成する。
+    fn expand(&self, test_name: &Ident, span: TokenStream2, body: TokenStream2) -> TokenStream2 {
+        let TestExpansion {
+            attrs,
+            tokio_test_attrs,
+            endpoint,
+            service_name,
+            guard_options,
+            output,
+            local,
+        } = self;
+
+        let guard_options = guard_options.builder_calls();
+        let await_block = if matches!(output, ReturnType::Default) {
+            quote! { execute_async_block.await; }
+        } else {
+            quote! { execute_async_block.await.unwrap(); }
+        };
+        // Err で終了した場合も root span の status が Error になるよう、root span の中で記録する。
+        let execute_body = match output {
+            ReturnType::Default => quote! { async { #body } },
+            ReturnType::Type(_, ty) => quote! {
+                async {
+                    let result: #ty = async { #body }.await;
+                    if let Err(e) = &result {
+                        otel_util::tracing::error!(error = ?e, "test returned an error");
+                    }
+                    result
+                }
+            },
+        };
+        let execute = if *local {
+            // actix-web のサービス等、Send でない値も扱えるよう LocalSet 上で実行する。
+            quote! {
+                let local_set = tokio::task::LocalSet::new();
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    local_set.spawn_local(async move {
+                        #await_block
+                    })
+                }));
+
+                let join_result = local_set.run_until(result.unwrap()).await;
+            }
+        } else {
+            quote! {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    tokio::spawn(async move {
+                        #await_block
+                    })
+                }));
+
+                let join_result = result.unwrap().await;
+            }
+        };
+
+        quote! {
+            #(#attrs)*
+            #tokio_test_attrs
+            async fn #test_name() {
+                // otel の初期化処理
+                let __otel_guard_for_otel_test;
+                {
+                    use otel_util::DefaultSimpleOtelGuardFactory;
+                    __otel_guard_for_otel_test = DefaultSimpleOtelGuardFactory::new(#endpoint, #service_name, "non-deployment")#guard_options.build();
+                }
+
+                // 関数 block の async 定義
+                use otel_util::tracing::Instrument;
+                let root_span = __otel_guard_for_otel_test.root_span(|| #span);
+                let root_trace_id = otel_util::span_trace_id(&root_span);
+                // otel_assert!(soft; ...) で記録した失敗は、テストの終了時にまとめて panic する。
+                let soft_assertions = otel_util::SoftAssertions::new();
+                let execute_async_block = soft_assertions.scope(#execute_body.instrument(root_span.clone()));
+
+                // 関数 block の async 実行と、panic-catch 部分
+                use std::panic::{self, AssertUnwindSafe};
+                #execute
+                let soft_assertion_failures = soft_assertions.take_summary();
+                // linked span 等、root span の外だけで失敗した場合も、root span を失敗として記録する。
+                if let Some(summary) = &soft_assertion_failures {
+                    root_span.in_scope(|| otel_util::tracing::error!("{}", summary));
+                }
+                drop(root_span);
+                // trace の送信は専用の runtime で行われるため、テストの runtime (start_paused 等) に関係なく送信完了まで待機できる。
+                __otel_guard_for_otel_test.force_flush();
+                let export_result = __otel_guard_for_otel_test.check_export_errors();
+                __otel_guard_for_otel_test.print_trace_link(
+                    root_trace_id.as_deref(),
+                    join_result.is_err() || soft_assertion_failures.is_some() || export_result.is_err(),
+                );
+
+                if join_result.is_err() {
+                    panic!("panic occurred");
+                }
+                if let Some(summary) = soft_assertion_failures {
+                    panic!("{}", summary);
+                }
+                if let Err(summary) = export_result {
+                    panic!("{}", summary);
                 }
             }
         }
-        UseOtelTestArgs {
+    }
+
+    // #[test] の同期関数を、body 全体を root span で計装するテスト関数として生成する。
+    fn expand_sync(
+        &self,
+        test_name: &Ident,
+        span: TokenStream2,
+        body: TokenStream2,
+    ) -> TokenStream2 {
+        let TestExpansion {
+            attrs,
             endpoint,
-            others: other_args,
+            service_name,
+            guard_options,
+            output,
+            ..
+        } = self;
+
+        let guard_options = guard_options.builder_calls();
+        let unwrap = match output {
+            ReturnType::Default => quote! {},
+            ReturnType::Type(..) => quote! { .unwrap() },
+        };
+
+        quote! {
+            #(#attrs)*
+            #[test]
+            fn #test_name() {
+                // otel の初期化処理
+                let __otel_guard_for_otel_test;
+                {
+                    use otel_util::DefaultSimpleOtelGuardFactory;
+                    __otel_guard_for_otel_test = DefaultSimpleOtelGuardFactory::new(#endpoint, #service_name, "non-deployment")#guard_options.build();
+                }
+
+                // 関数 block の実行と、panic-catch 部分
+                use std::panic::{self, AssertUnwindSafe};
+                let root_span = __otel_guard_for_otel_test.root_span(|| #span);
+                let root_trace_id = otel_util::span_trace_id(&root_span);
+                let soft_assertions = otel_util::SoftAssertions::new();
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    soft_assertions.in_scope(|| {
+                        let _entered = root_span.enter();
+                        let execute_block = || #output #body;
+                        execute_block()#unwrap;
+                    })
+                }));
+
+                let soft_assertion_failures = soft_assertions.take_summary();
+                // linked span 等、root span の外だけで失敗した場合も、root span を失敗として記録する。
+                if let Some(summary) = &soft_assertion_failures {
+                    root_span.in_scope(|| otel_util::tracing::error!("{}", summary));
+                }
+                drop(root_span);
+                __otel_guard_for_otel_test.force_flush();
+                let export_result = __otel_guard_for_otel_test.check_export_errors();
+                __otel_guard_for_otel_test.print_trace_link(
+                    root_trace_id.as_deref(),
+                    result.is_err() || soft_assertion_failures.is_some() || export_result.is_err(),
+                );
+
+                if result.is_err() {
+                    panic!("panic occurred");
+                }
+                if let Some(summary) = soft_assertion_failures {
+                    panic!("{}", summary);
+                }
+                if let Err(summary) = export_result {
+                    panic!("{}", summary);
+                }
+            }
+        }
+    }
+}
+
+// 各ケースを、テスト名の module 配下の case_N というテストとして展開する。
+// root span 名は <test>::case_N とし (test.name にも記録する)、引数の値は評価した後に test.case.<引数名> の attribute として (Debug 形式で) 記録する。
+fn expand_cases(input: &ItemFn, cases: &[TestCase], expansion: &TestExpansion) -> TokenStream2 {
+    let fn_name = &input.sig.ident;
+    let block = &input.block;
+    let case_fns = cases.iter().enumerate().map(|(i, case)| {
+        let case_name = format_ident!("case_{}", i + 1);
+        let span_name = LitStr::new(&format!("{}::{}", fn_name, case_name), fn_name.span());
+
+        let mut bindings = Vec::new();
+        let mut fields = Vec::new();
+        for (j, (input, value)) in input.sig.inputs.iter().zip(case.args.iter()).enumerate() {
+            if let FnArg::Typed(PatType { pat, ty, .. }) = input {
+                let field = match pat.as_ref() {
+                    Pat::Ident(pat_ident) => pat_ident.ident.clone(),
+                    _ => format_ident!("arg_{}", j),
+                };
+                let field_name = LitStr::new(&format!("test.case.{}", field), field.span());
+                let binding = format_ident!("__otel_case_arg_{}", j);
+                // 定数や式の場合も、評価した値を記録する。
+                bindings.push(quote! {
+                    let #binding: #ty = #value;
+                    otel_util::tracing::Span::current().record(#field_name, otel_util::tracing::field::debug(&#binding));
+                    let #pat = #binding;
+                });
+                fields.push(quote! { test.case.#field = otel_util::tracing::field::Empty });
+            }
+        }
+
+        expansion.expand(
+            &case_name,
+            quote! { otel_util::tracing::info_span!(#span_name, test.name = #span_name, #(#fields),*) },
+            quote! {
+                #(#bindings)*
+                #block
+            },
+        )
+    });
+
+    quote! {
+        mod #fn_name {
+            use super::*;
+
+            #(#case_fns)*
         }
     }
 }
 
 #[proc_macro_attribute]
 pub fn use_otel_at_test(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(item as ItemFn);
+    let mut input = parse_macro_input!(item as ItemFn);
+    let cases = match take_cases(&mut input) {
+        Ok(cases) => cases,
+        Err(e) => return e.to_compile_error().into(),
+    };
     let fn_name = &input.sig.ident;
     let attrs = &input.attrs;
-    let return_type = &input.sig.output;
-    let is_default_type = *return_type == syn::ReturnType::Default;
     let block = &input.block;
 
     let args = parse_macro_input!(_attr as AttributeArgs);
-    let my_args = UseOtelTestArgs::new(args);
-    let (endpoint, other_args) = (my_args.endpoint, my_args.others);
+    let my_args = match (
+        UseOtelTestArgs::new(args),
+        validate_signature(&input, &cases),
+    ) {
+        (Ok(args), Ok(())) => args,
+        (Err(mut e), Err(sig_e)) => {
+            e.combine(sig_e);
+            return e.to_compile_error().into();
+        }
+        (Err(e), _) | (_, Err(e)) => return e.to_compile_error().into(),
+    };
+    let (endpoint, local, guard_options, other_args) = (
+        my_args.endpoint,
+        my_args.local,
+        my_args.guard_options,
+        my_args.others,
+    );
 
     let tokio_test_attrs = match other_args.len() {
         0 => quote! { #[tokio::test] },
@@ -50,45 +467,47 @@ pub fn use_otel_at_test(_attr: TokenStream, item: TokenStream) -> TokenStream {
         }
     };
 
-    let await_block = if is_default_type {
-        quote! { execute_async_block.await; }
-    } else {
-        quote! { execute_async_block.await.unwrap(); }
+    let service_name = fn_name.to_string();
+    let expansion = TestExpansion {
+        attrs,
+        tokio_test_attrs: &tokio_test_attrs,
+        endpoint: &endpoint,
+        service_name: &service_name,
+        guard_options: &guard_options,
+        output: &input.sig.output,
+        local,
     };
 
-    let expanded = quote! {
-        #(#attrs)*
-        #tokio_test_attrs
-        async fn #fn_name() {
-            // otel の初期化処理
-            let __otel_guard_for_otel_test;
-            {
-                use otel_util::DefaultSimpleOtelGuardFactory;
-                __otel_guard_for_otel_test = DefaultSimpleOtelGuardFactory::new(#endpoint, stringify!(#fn_name), "non-deployment").build();
-            }
-
-            // 関数 block の async 定義
-            use otel_util::tracing::Instrument;
-            let execute_async_block = async {
-                #block
-            }.instrument(tracing::info_span!(stringify!(#fn_name)));
-
-            // 関数 block の async 実行と、panic-catch 部分
-            use otel_util::tokio::time::{sleep, Duration};
-            use std::panic::{self, AssertUnwindSafe};
-            let result = panic::catch_unwind(AssertUnwindSafe(|| {
-                tokio::spawn(async move {
-                    #await_block
-                })
-            }));
-
-            let join_result = result.unwrap().await;
-            sleep(Duration::from_secs(1)).await; // trace の送信の前に、待機しないと、trace が送信されない。
-
-            if join_result.is_err() {
-                panic!("panic occurred");
-            }
-        }
+    let expanded = if cases.is_empty() {
+        expansion.expand(
+            fn_name,
+            quote! { otel_util::tracing::info_span!(stringify!(#fn_name), test.name = stringify!(#fn_name)) },
+            quote! { #block },
+        )
+    } else {
+        expand_cases(&input, &cases, &expansion)
     };
     TokenStream::from(expanded)
 }
+
+// module 内の #[tokio::test] (sync_tests = true の場合は #[test] も) を、use_otel_at_test と同じ形に計装する。
+#[proc_macro_attribute]
+pub fn otel_tests(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as AttributeArgs);
+    let input = parse_macro_input!(item as ItemMod);
+    match otel_tests::expand(args, input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+// module や impl block 内の関数すべてに #[tracing::instrument] を付与する。Result を返す関数には err も付与する。
+#[proc_macro_attribute]
+pub fn instrument_all(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as AttributeArgs);
+    let input = parse_macro_input!(item as syn::Item);
+    match instrument_all::expand(args, input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
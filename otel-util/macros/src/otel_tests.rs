@@ -0,0 +1,190 @@
+use super::guard_options::{GuardOptions, GUARD_OPTION_KEYS};
+use super::TestExpansion;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{Attribute, AttributeArgs, Error, Item, ItemFn, ItemMod, Lit, Meta, NestedMeta};
+
+// module 内のテストで共通の設定。
+struct OtelTestsArgs {
+    endpoint: String,
+    service_name_prefix: String,
+    sync_tests: bool,
+    local: bool,
+    guard_options: GuardOptions,
+}
+
+impl OtelTestsArgs {
+    fn new(args: AttributeArgs) -> syn::Result<Self> {
+        let mut endpoint = "grpc://localhost:4317".to_string();
+        let mut service_name_prefix = String::new();
+        let mut sync_tests = false;
+        let mut local = false;
+        let mut guard_options = GuardOptions::default();
+        let mut seen = Vec::<String>::new();
+        let mut errors: Option<Error> = None;
+        let mut push_error = |error: Error| match errors.as_mut() {
+            Some(errors) => errors.combine(error),
+            None => errors = Some(error),
+        };
+
+        for arg in args {
+            let nv = match &arg {
+                NestedMeta::Meta(Meta::NameValue(nv)) => nv,
+                _ => {
+                    push_error(Error::new_spanned(
+                        &arg,
+                        "expected `key = value`, e.g. `endpoint = \"grpc://localhost:4317\"`",
+                    ));
+                    continue;
+                }
+            };
+            let key = nv.path.to_token_stream().to_string();
+            if seen.contains(&key) {
+                push_error(Error::new_spanned(
+                    &nv.path,
+                    format!("duplicated argument `{}`", key),
+                ));
+                continue;
+            }
+            seen.push(key.clone());
+
+            match (key.as_str(), &nv.lit) {
+                ("endpoint", Lit::Str(s)) => endpoint = s.value(),
+                ("service_name_prefix", Lit::Str(s)) => service_name_prefix = s.value(),
+                ("sync_tests", Lit::Bool(b)) => sync_tests = b.value,
+                ("local", Lit::Bool(b)) => local = b.value,
+                (key, lit) if GuardOptions::is_key(key) => {
+                    if let Err(e) = guard_options.parse(key, lit) {
+                        push_error(e);
+                    }
+                }
+                ("endpoint" | "service_name_prefix", lit) => push_error(Error::new_spanned(
+                    lit,
+                    format!("`{}` must be a string literal", key),
+                )),
+                ("sync_tests" | "local", lit) => push_error(Error::new_spanned(
+                    lit,
+                    format!("`{}` must be a boolean literal", key),
+                )),
+                _ => push_error(Error::new_spanned(
+                    &nv.path,
+                    format!(
+                        "unknown argument `{}`; expected one of `endpoint`, `service_name_prefix`, `sync_tests`, `local`, {}",
+                        key,
+                        GUARD_OPTION_KEYS
+                            .iter()
+                            .map(|k| format!("`{}`", k))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                )),
+            }
+        }
+
+        match errors {
+            Some(errors) => Err(errors),
+            None => Ok(OtelTestsArgs {
+                endpoint,
+                service_name_prefix,
+                sync_tests,
+                local,
+                guard_options,
+            }),
+        }
+    }
+}
+
+fn is_tokio_test(attr: &Attribute) -> bool {
+    let segments: Vec<String> = attr
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    segments == ["tokio", "test"]
+}
+
+fn is_use_otel_at_test(attr: &Attribute) -> bool {
+    attr.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "use_otel_at_test")
+}
+
+pub(crate) fn expand(args: AttributeArgs, mut input: ItemMod) -> syn::Result<TokenStream2> {
+    let args = OtelTestsArgs::new(args)?;
+    let Some((_, items)) = input.content.as_mut() else {
+        return Err(Error::new_spanned(
+            &input,
+            "`#[otel_tests]` can only be applied to an inline module, e.g. `mod tests { ... }`",
+        ));
+    };
+
+    for item in items.iter_mut() {
+        if let Item::Fn(item_fn) = item {
+            if let Some(expanded) = instrument_test_fn(&args, item_fn)? {
+                *item = Item::Verbatim(expanded);
+            }
+        }
+    }
+    Ok(input.into_token_stream())
+}
+
+// テスト関数であれば計装したものを返す。テスト関数でない、または計装済みの場合は None を返す。
+fn instrument_test_fn(args: &OtelTestsArgs, item_fn: &ItemFn) -> syn::Result<Option<TokenStream2>> {
+    if item_fn.attrs.iter().any(is_use_otel_at_test) {
+        return Ok(None);
+    }
+    let Some(position) = item_fn
+        .attrs
+        .iter()
+        .position(|attr| is_tokio_test(attr) || (args.sync_tests && attr.path.is_ident("test")))
+    else {
+        return Ok(None);
+    };
+
+    let mut attrs = item_fn.attrs.clone();
+    let test_attr = attrs.remove(position);
+    let is_async = item_fn.sig.asyncness.is_some();
+    if is_tokio_test(&test_attr) && !is_async {
+        return Err(Error::new_spanned(
+            item_fn.sig.fn_token,
+            "the `async` keyword is missing from the function declaration",
+        ));
+    }
+    if !is_tokio_test(&test_attr) && is_async {
+        return Err(Error::new_spanned(
+            &test_attr,
+            "`#[test]` cannot be applied to `async fn`; use `#[tokio::test]` instead",
+        ));
+    }
+    if !item_fn.sig.inputs.is_empty() {
+        return Err(Error::new_spanned(
+            &item_fn.sig.inputs,
+            "the test function cannot accept arguments",
+        ));
+    }
+
+    let fn_name = &item_fn.sig.ident;
+    let block = &item_fn.block;
+    let service_name = format!("{}{}", args.service_name_prefix, fn_name);
+    let tokio_test_attrs = test_attr.to_token_stream();
+    let expansion = TestExpansion {
+        attrs: &attrs,
+        tokio_test_attrs: &tokio_test_attrs,
+        endpoint: &args.endpoint,
+        service_name: &service_name,
+        guard_options: &args.guard_options,
+        output: &item_fn.sig.output,
+        local: args.local,
+    };
+    let span = quote! {
+        otel_util::tracing::info_span!(stringify!(#fn_name), test.name = stringify!(#fn_name))
+    };
+
+    Ok(Some(if is_async {
+        expansion.expand(fn_name, span, quote! { #block })
+    } else {
+        expansion.expand_sync(fn_name, span, quote! { #block })
+    }))
+}
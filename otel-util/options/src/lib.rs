@@ -0,0 +1,53 @@
+// 環境変数と macro の引数で共通の、設定値の名前。
+// macros (引数の検証) と cores (実行時の解釈) の両方が、同じ一覧を使う。
+
+// OTEL_PROPAGATORS と同じ名前。順序は PropagatorKind::ALL と同じ。
+pub const PROPAGATOR_NAMES: [&str; 5] = ["tracecontext", "baggage", "b3", "b3multi", "jaeger"];
+
+// PROPAGATOR_NAMES の中での位置を返す。
+pub fn propagator_index(name: &str) -> Result<usize, String> {
+    PROPAGATOR_NAMES
+        .iter()
+        .position(|n| *n == name.trim())
+        .ok_or_else(|| {
+            format!(
+                "unknown propagator `{}`; expected one of {}",
+                name.trim(),
+                PROPAGATOR_NAMES
+                    .iter()
+                    .map(|n| format!("`{}`", n))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+}
+
+// "tracecontext,baggage,b3" のようなカンマ区切りの指定を、PROPAGATOR_NAMES の位置の一覧にする。"none" は propagator なしを表す。
+pub fn propagator_indexes(s: &str) -> Result<Vec<usize>, String> {
+    if s.trim() == "none" {
+        return Ok(Vec::new());
+    }
+    s.split(',').map(propagator_index).collect()
+}
+
+// OTEL_TEST_FALLBACK_SINK と同じ形式の、fallback の送り先。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FallbackName<'a> {
+    NoOp,
+    Stdout,
+    File(&'a str),
+}
+
+pub fn parse_fallback_name(s: &str) -> Result<FallbackName<'_>, String> {
+    match s.trim() {
+        "noop" => Ok(FallbackName::NoOp),
+        "stdout" => Ok(FallbackName::Stdout),
+        other => match other.strip_prefix("file:") {
+            Some(path) if !path.is_empty() => Ok(FallbackName::File(path)),
+            _ => Err(format!(
+                "unknown fallback sink `{}`; expected `noop`, `stdout` or `file:<path>`",
+                other
+            )),
+        },
+    }
+}
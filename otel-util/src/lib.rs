@@ -1,3 +1,6 @@
 pub use cores::*;
 pub use deps::*;
 pub use macros::*;
+// deps::rayon (crate) より、span を引き継ぐ cores::rayon を優先する。
+#[cfg(feature = "rayon")]
+pub use cores::rayon;